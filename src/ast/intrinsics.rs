@@ -12,10 +12,44 @@ pub enum Intrinsic {
 	MulNum,
 	DivNum,
 	ExpNum,
+	NegNum,
+	AddInt,
+	SubInt,
+	MulInt,
+	DivInt,
+	NegInt,
+	AddBig,
+	SubBig,
+	MulBig,
+	DivBig,
+	NegBig,
+	BigToStr,
+	ParseBig,
+	StrLen,
+	StrCharAt,
+	StrSlice,
+	CharToStr,
+	ToStr,
 
 	EqStr,
 	EqNum,
+	EqInt,
+	EqBig,
+	EqChar,
 	EqBool,
+
+	LtNum,
+	LeNum,
+	GtNum,
+	GeNum,
+	LtInt,
+	LeInt,
+	GtInt,
+	GeInt,
+	LtBig,
+	LeBig,
+	GtBig,
+	GeBig,
 }
 
 impl Intrinsic {
@@ -28,10 +62,44 @@ impl Intrinsic {
 			"INTRINSIC_MUL_NUM" => Some(Self::MulNum),
 			"INTRINSIC_DIV_NUM" => Some(Self::DivNum),
 			"INTRINSIC_EXP_NUM" => Some(Self::ExpNum),
+			"INTRINSIC_NEG_NUM" => Some(Self::NegNum),
+			"INTRINSIC_ADD_INT" => Some(Self::AddInt),
+			"INTRINSIC_SUB_INT" => Some(Self::SubInt),
+			"INTRINSIC_MUL_INT" => Some(Self::MulInt),
+			"INTRINSIC_DIV_INT" => Some(Self::DivInt),
+			"INTRINSIC_NEG_INT" => Some(Self::NegInt),
+			"INTRINSIC_ADD_BIG" => Some(Self::AddBig),
+			"INTRINSIC_SUB_BIG" => Some(Self::SubBig),
+			"INTRINSIC_MUL_BIG" => Some(Self::MulBig),
+			"INTRINSIC_DIV_BIG" => Some(Self::DivBig),
+			"INTRINSIC_NEG_BIG" => Some(Self::NegBig),
+			"INTRINSIC_BIG_TO_STR" => Some(Self::BigToStr),
+			"INTRINSIC_PARSE_BIG" => Some(Self::ParseBig),
+			"INTRINSIC_STR_LEN" => Some(Self::StrLen),
+			"INTRINSIC_STR_CHAR_AT" => Some(Self::StrCharAt),
+			"INTRINSIC_STR_SLICE" => Some(Self::StrSlice),
+			"INTRINSIC_CHAR_TO_STR" => Some(Self::CharToStr),
+			"INTRINSIC_TO_STR" => Some(Self::ToStr),
 
 			"INTRINSIC_EQ_NUM" => Some(Self::EqNum),
 			"INTRINSIC_EQ_STR" => Some(Self::EqStr),
+			"INTRINSIC_EQ_INT" => Some(Self::EqInt),
+			"INTRINSIC_EQ_BIG" => Some(Self::EqBig),
+			"INTRINSIC_EQ_CHAR" => Some(Self::EqChar),
 			"INTRINSIC_EQ_BOOL" => Some(Self::EqBool),
+
+			"INTRINSIC_LT_NUM" => Some(Self::LtNum),
+			"INTRINSIC_LE_NUM" => Some(Self::LeNum),
+			"INTRINSIC_GT_NUM" => Some(Self::GtNum),
+			"INTRINSIC_GE_NUM" => Some(Self::GeNum),
+			"INTRINSIC_LT_INT" => Some(Self::LtInt),
+			"INTRINSIC_LE_INT" => Some(Self::LeInt),
+			"INTRINSIC_GT_INT" => Some(Self::GtInt),
+			"INTRINSIC_GE_INT" => Some(Self::GeInt),
+			"INTRINSIC_LT_BIG" => Some(Self::LtBig),
+			"INTRINSIC_LE_BIG" => Some(Self::LeBig),
+			"INTRINSIC_GT_BIG" => Some(Self::GtBig),
+			"INTRINSIC_GE_BIG" => Some(Self::GeBig),
 			_ => None
 		}
 	}
@@ -47,10 +115,35 @@ impl<T: Clone> HasType<T> for Intrinsic {
 			Self::MulNum => Ok(TypeData::Number),
 			Self::DivNum => Ok(TypeData::Number),
 			Self::ExpNum => Ok(TypeData::Number),
+			Self::NegNum => Ok(TypeData::Number),
+			Self::AddInt => Ok(TypeData::Integer),
+			Self::SubInt => Ok(TypeData::Integer),
+			Self::MulInt => Ok(TypeData::Integer),
+			Self::DivInt => Ok(TypeData::Integer),
+			Self::NegInt => Ok(TypeData::Integer),
+			Self::AddBig => Ok(TypeData::BigInt),
+			Self::SubBig => Ok(TypeData::BigInt),
+			Self::MulBig => Ok(TypeData::BigInt),
+			Self::DivBig => Ok(TypeData::BigInt),
+			Self::NegBig => Ok(TypeData::BigInt),
+			Self::BigToStr => Ok(TypeData::String),
+			Self::ParseBig => Ok(TypeData::BigInt),
+			Self::StrLen => Ok(TypeData::Integer),
+			Self::StrCharAt => Ok(TypeData::Char),
+			Self::StrSlice => Ok(TypeData::String),
+			Self::CharToStr => Ok(TypeData::String),
+			Self::ToStr => Ok(TypeData::String),
 
 			Self::EqBool => Ok(TypeData::Bool),
 			Self::EqNum => Ok(TypeData::Bool),
 			Self::EqStr => Ok(TypeData::Bool),
+			Self::EqInt => Ok(TypeData::Bool),
+			Self::EqBig => Ok(TypeData::Bool),
+			Self::EqChar => Ok(TypeData::Bool),
+
+			Self::LtNum | Self::LeNum | Self::GtNum | Self::GeNum => Ok(TypeData::Bool),
+			Self::LtInt | Self::LeInt | Self::GtInt | Self::GeInt => Ok(TypeData::Bool),
+			Self::LtBig | Self::LeBig | Self::GtBig | Self::GeBig => Ok(TypeData::Bool),
 		}
 	}
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::lint::Level;
 use crate::span::Span;
 
 use super::expr::Expr;
@@ -11,26 +12,53 @@ pub enum Item {
 		Vec<(Span<Ident>, Span<TypeData>)>,
 		Span<TypeData>,
 		Span<Expr>,
+		Attributes,
 	),
-	TraitDef(Span<Ident>, Trait),
-	ImplTrait(Span<TypeData>, ImplTrait),
+	TraitDef(Span<Ident>, Trait, Attributes),
+	ImplTrait(Span<TypeData>, ImplTrait, Attributes),
+}
+
+/// What is written before an item, its doc comments and attributes
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Attributes {
+	/// Lines of the `///` doc comments
+	pub docs: Vec<String>,
+	/// Lint levels set with `#[allow(lint)]`, `#[warn(lint)]` and `#[deny(lint)]`, in order
+	pub lints: Vec<(Level, Span<Ident>)>,
 }
 
 impl Item {
 	pub fn get_type(&self) -> Option<TypeData> {
 		match self {
-			Self::Fn(_, args, ret, _) => {
+			Self::Fn(_, args, ret, _, _) => {
 				Some(TypeData::Fn(FnSignature(args.clone(), Box::new(ret.clone()))))
 			},
 			_ => None
 		}
 	}
+
+	pub fn attributes(&self) -> &Attributes {
+		match self {
+			Self::Fn(_, _, _, _, attrs) | Self::TraitDef(_, _, attrs) | Self::ImplTrait(_, _, attrs) => attrs,
+		}
+	}
+
+	/// Lines of the `///` doc comments written before the item
+	pub fn docs(&self) -> &[String] {
+		&self.attributes().docs
+	}
 }
 
 impl std::fmt::Display for Item {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+		for line in self.docs() {
+			writeln!(f, "/// {}", line)?;
+		}
+		for (level, lint) in &self.attributes().lints {
+			writeln!(f, "#[{}({})]", level, lint.as_ref())?;
+		}
 		match self {
-			Self::Fn(name, args, return_type, body) => write!(
+			Self::Fn(name, args, return_type, body, _) => write!(
 				f,
 				"fn {}({}) -> {} {}",
 				name,
@@ -41,8 +69,8 @@ impl std::fmt::Display for Item {
 				return_type,
 				body
 			),
-			Self::TraitDef(_, t) => write!(f, "{}", t),
-			Self::ImplTrait(t, impl_trait) => write!(f, "impl {} for {}", impl_trait, t)
+			Self::TraitDef(_, t, _) => write!(f, "{}", t),
+			Self::ImplTrait(t, impl_trait, _) => write!(f, "impl {} for {}", impl_trait, t)
 		}
 	}
 }
@@ -15,23 +15,6 @@ pub struct FnSignature(
 	pub Box<Span<TypeData>>,
 );
 
-impl FnSignature {
-	pub fn matches_args(&self, args_other: &[TypeData]) -> bool {
-		let FnSignature(args_self, _) = self;
-		let mut ret = true;
-		let mut i = 0;
-		while let (Some((_, typ1)), Some(typ2), true) = (args_self.get(i), args_other.get(i), ret) {
-			ret = ret && typ1.as_ref() == typ2;
-			i += 1;
-		}
-		ret
-	}
-
-	pub fn args_types_tuple(&self) -> Type {
-		TypeData::Tuple(self.0.iter().map(|(_, t)| t.val()).collect()).default_type()
-	}
-}
-
 impl PartialEq for FnSignature {
 	fn eq(&self, rhs: &Self) -> bool {
 		let FnSignature(args_self, ret_self) = self;
@@ -78,6 +61,11 @@ impl Type {
 		// println!("Traits: {:?}", self.traits);
 	}
 
+	/// The traits implemented by the type
+	pub fn impl_traits(&self) -> &[ImplTrait] {
+		&self.traits
+	}
+
 	pub fn get_impl_trait(&self, name: &str, defining_types: &[&Type]) -> Option<&ImplTrait> {
 		// println!("{} traits: {:?}", self, self.traits);
 		for t in &self.traits {
@@ -111,15 +99,22 @@ impl std::fmt::Display for Type {
 	}
 }
 
+/// Names of the types that are always defined
+pub const PRIMITIVE_TYPES: &[&str] = &["number", "integer", "bigint", "char", "string", "void", "bool"];
+
 #[derive(Clone, Debug, Hash, Eq)]
 pub enum TypeData {
 	Bool,
 	Number,
+	Integer,
+	BigInt,
+	Char,
 	String,
 	Array(Box<TypeData>),
 	Fn(FnSignature),
 	Tuple(Vec<TypeData>),
 	Void,
+	/// Type of an expression with an error, it's accepted by everything so each error is only reported once
 	Err,
 	Never,
 	SelfRef,
@@ -134,12 +129,39 @@ impl TypeData {
 			traits: vec![],
 		}
 	}
+
+	pub fn is_primitive(&self) -> bool {
+		matches!(
+			self,
+			Self::Bool | Self::Number | Self::Integer | Self::BigInt | Self::Char | Self::String
+		)
+	}
+
+	/// Checks if a value of this type can be converted with `as` into `other`.
+	/// Only primitive types can be casted, user types are always rejected. A cast to the same type does nothing,
+	/// the `trivial_casts` lint reports it
+	pub fn can_cast_to(&self, other: &TypeData) -> bool {
+		(self.is_primitive() && self == other)
+			|| matches!(
+			(self, other),
+			(Self::Number, Self::Integer)
+				| (Self::Integer, Self::Number)
+				| (Self::Bool, Self::Integer)
+				| (Self::Integer, Self::Char)
+				| (Self::Char, Self::Integer)
+				| (Self::Integer, Self::BigInt)
+				| (Self::BigInt, Self::Integer)
+			)
+	}
 }
 
 impl PartialEq for TypeData {
 	fn eq(&self, rhs: &Self) -> bool {
 		match (self, rhs) {
 			(Self::Number, Self::Number) => true,
+			(Self::Integer, Self::Integer) => true,
+			(Self::BigInt, Self::BigInt) => true,
+			(Self::Char, Self::Char) => true,
 			(Self::String, Self::String) => true,
 			(Self::Void, Self::Void) => true,
 			(Self::Err, Self::Err) => true,
@@ -162,6 +184,9 @@ impl std::fmt::Display for TypeData {
 		match self {
 			Self::Bool=> write!(f, "bool"),
 			Self::Number => write!(f, "number"),
+			Self::Integer => write!(f, "integer"),
+			Self::BigInt => write!(f, "bigint"),
+			Self::Char => write!(f, "char"),
 			Self::String => write!(f, "string"),
 			Self::Array(typ) => write!(f, "[{}]", typ),
 			Self::Fn(x) => write!(f, "{:?}", x),
@@ -186,8 +211,7 @@ impl std::fmt::Display for TypeData {
 pub enum TypeError {
 	TraitNotImplemented(String, Vec<Type>, TypeData),
 	// BranchesDontMatch,
-	IdentNotFound(Ident),
-	Err(Error),
+	InvalidCast(TypeData, TypeData),
 }
 
 pub trait HasType<T: Clone> {
@@ -237,12 +261,13 @@ impl ImplTrait {
 		}
 	}
 
-	// pub fn methods(&self) -> &HashMap<Ident, (FnSignature, Span<Expr>)> {
-	// 	&self.methods
-	// }
+	pub fn methods(&self) -> &HashMap<Ident, (FnSignature, Span<Expr>)> {
+		&self.methods
+	}
 
-	pub fn get_method(&self, id: &Ident) -> Option<&(FnSignature, Span<Expr>)> {
-		self.methods.get(id)
+	/// Names of the types defined by the implementation, like `Output` for `type Output = number;`
+	pub fn typedef_names(&self) -> impl Iterator<Item = &str> {
+		self.typedefs.keys().map(String::as_str)
 	}
 
 	pub fn matches(&self, definfing_types: &[&Type]) -> bool {
@@ -261,6 +286,15 @@ impl ImplTrait {
 		&self.based_on.as_ref()
 	}
 
+	pub fn trait_name_span(&self) -> &Span<Ident> {
+		&self.based_on
+	}
+
+	/// The types the trait is implemented for, like `number` in `impl Add<number> for number`
+	pub fn defining_types(&self) -> Vec<TypeData> {
+		self.defining_types.iter().map(|t| t.as_ref().type_data().clone()).collect()
+	}
+
 	pub fn trait_name_string(&self) -> &String {
 		self.based_on.as_ref()
 	}
@@ -272,16 +306,17 @@ impl ImplTrait {
 	pub fn matches_trait<
 		F: FnMut(
 			&TypeDB,
+			&Ident,
 			Vec<(Span<Ident>, Span<TypeData>)>,
 			Span<TypeData>,
 			Span<Expr>,
-		) -> Result<(), Error>,
+		),
 	>(
 		&self,
 		t: &Trait,
 		type_db: &TypeDB,
 		mut checker_fn: F,
-	) -> Result<bool, Error> {
+	) -> bool {
 		let mut type_db = type_db.clone().push();
 		// println!(
 		// 	"Checking if impl trait {} matches {}",
@@ -296,7 +331,7 @@ impl ImplTrait {
 		// TODO give better errors
 		if ret == false {
 			println!("Names or defining types len don't match");
-			return Ok(false);
+			return false;
 		}
 		for (self_k, self_t) in &self.typedefs {
 			ret = ret
@@ -305,7 +340,7 @@ impl ImplTrait {
 					.position(|x| x.as_ref() == self_k)
 					.is_some();
 			if ret == false {
-				return Ok(false);
+				return false;
 			}
 			type_db.set(TypeData::Other(self_k.clone()), self_t.val())
 		}
@@ -354,15 +389,15 @@ impl ImplTrait {
 					.unwrap_or(false);
 			//println!("{}", ret);
 			if ret == false {
-				return Ok(false);
+				return false;
 			}
 			{
 				let FnSignature(args, ret) = self_signature;
-				checker_fn(&type_db, args.clone(), *ret.clone(), self_body.clone())?;
+				checker_fn(&type_db, self_method_name, args.clone(), *ret.clone(), self_body.clone());
 			}
 		}
 		//println!("{}", ret);
-		Ok(ret)
+		ret
 	}
 }
 
@@ -421,6 +456,19 @@ impl Trait {
 			methods,
 		}
 	}
+
+	pub fn name(&self) -> &Span<Ident> {
+		&self.name
+	}
+
+	pub fn methods(&self) -> &HashMap<Ident, FnSignature> {
+		&self.methods
+	}
+
+	/// Names of the types the trait defines, its defining types and its typedefs, like `Rhs` and `Output`
+	pub fn type_names(&self) -> impl Iterator<Item = &str> {
+		self.defining_types.iter().chain(&self.typedefs).map(|t| t.as_ref().as_str())
+	}
 }
 
 impl std::fmt::Debug for Trait {
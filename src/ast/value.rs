@@ -1,17 +1,22 @@
 use crate::scope::{Scope, TypeDB};
+use num_bigint::BigInt;
 use crate::span::Span;
 use crate::error::Error;
 
 use super::types::{HasType, TypeData, TypeError};
-use super::expr::Expr;
+use super::Ident;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
 	True,
 	False,
 	Num(f64),
+	Int(i64),
+	BigInt(BigInt),
+	Char(char),
 	Str(String),
-	Fn(TypeData, Box<Expr>),
+	/// A function defined as an item, by its name
+	Fn(TypeData, Ident),
 	Never,
 	Void,
 }
@@ -22,6 +27,9 @@ impl<T: Clone> HasType<T> for Value {
 			Self::True => TypeData::Bool,
 			Self::False => TypeData::Bool,
 			Self::Num(_) => TypeData::Number,
+			Self::Int(_) => TypeData::Integer,
+			Self::BigInt(_) => TypeData::BigInt,
+			Self::Char(_) => TypeData::Char,
 			Self::Str(_) => TypeData::String,
 			Self::Fn(t, _) => t.clone(),
 			Self::Never => TypeData::Never,
@@ -34,6 +42,9 @@ impl std::fmt::Display for Value {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
 		match self {
 			Self::Num(n) => write!(f, "{}", n),
+			Self::Int(n) => write!(f, "{}", n),
+			Self::BigInt(n) => write!(f, "{}n", n),
+			Self::Char(c) => write!(f, "'{}'", c),
 			Self::Str(s) => write!(f, "\"{}\"", s),
 			Self::Fn(t, b) => write!(f, "{} {}", t, b),
 			Self::Void => write!(f, "void"),
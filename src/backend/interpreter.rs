@@ -1,13 +1,18 @@
-use crate::{ast::Item, interpreter, options::CodegenOptions, scope::TypeDB, span::Span, error};
+use crate::{hir, interpreter, options::CodegenOptions, error, emitter::Emitter};
 
 pub struct Codegen;
 
 impl super::Backend for Codegen {
-    fn gen_code(&self, items: &[Span<Item>], type_db: &mut TypeDB, opts: CodegenOptions) {
-        if opts.lib {
-            error::non_located_error_default("Can't interpret a library, only code with a main() function is interpretable", error::ErrorKind::Error(error::ReturnValue::NoMain.into()))
-        }else{
-            interpreter::interpret_items(items, type_db)
-        }
+    fn gen_code(&self, program: &hir::Program, opts: CodegenOptions, emitter: &mut Emitter) -> Result<(), error::ReturnValue> {
+        let (info, r) = if opts.lib {
+            ("Can't interpret a library, only code with a main() function is interpretable".to_string(), error::ReturnValue::NoMain)
+        } else {
+            match interpreter::interpret(program) {
+                Ok(()) => return Ok(()),
+                Err(e) => (e, error::ReturnValue::RuntimeError),
+            }
+        };
+        emitter.emit_non_located(info, error::ErrorKind::Error(r.into()));
+        Err(r)
     }
 }
\ No newline at end of file
@@ -1,11 +1,11 @@
 use std::path::PathBuf;
 
-use crate::{ast::Item, options::CodegenOptions, scope::TypeDB, span::Span};
+use crate::{emitter::Emitter, error::ReturnValue, hir, options::CodegenOptions};
 
 pub struct Codegen;
 
 impl super::Backend for Codegen {
-    fn gen_code(&self, items: &[Span<Item>], type_db: &mut TypeDB, options: CodegenOptions) {
-        
+    fn gen_code(&self, program: &hir::Program, options: CodegenOptions, _emitter: &mut Emitter) -> Result<(), ReturnValue> {
+        Ok(())
     }
 }
\ No newline at end of file
@@ -1,10 +1,11 @@
 use std::path::PathBuf;
 
-use crate::{ast::Item, scope::TypeDB, span::Span, options::CodegenOptions};
+use crate::{emitter::Emitter, error::ReturnValue, hir, options::CodegenOptions};
 
 pub mod js;
 pub mod interpreter;
 
 pub trait Backend {
-	fn gen_code(&self, items: &[Span<Item>], type_db: &mut TypeDB, options: CodegenOptions);
-}
\ No newline at end of file
+	/// The errors are written with `emitter`, the one that stops the backend gives what the compiler exits with
+	fn gen_code(&self, program: &hir::Program, options: CodegenOptions, emitter: &mut Emitter) -> Result<(), ReturnValue>;
+}
@@ -0,0 +1,241 @@
+//! Control flow graph of a function body.
+//! Finds the statements that can't be reached and whether the end of the body is reached without a value
+use crate::ast::TypeData;
+use crate::hir::{self, ExprKind};
+use crate::span::location::Location;
+use crate::span::{HasLoc, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NodeId(usize);
+
+const ENTRY: NodeId = NodeId(0);
+const RETURN: NodeId = NodeId(1);
+const FALL_OFF: NodeId = NodeId(2);
+
+enum Node {
+	/// Start of the function
+	Entry,
+	/// The function gives a value
+	Return,
+	/// The end of the body is reached without giving a value
+	FallOff,
+	/// A statement of a block
+	Stmt(Span<()>),
+	/// Where the statements after a nested block continue
+	Join,
+}
+
+pub struct Cfg {
+	nodes: Vec<Node>,
+	/// Successors of every node
+	edges: Vec<Vec<NodeId>>,
+	/// The statements of every block, in order
+	blocks: Vec<Vec<NodeId>>,
+}
+
+impl Cfg {
+	pub fn build(body: &Span<hir::Expr>) -> Self {
+		let mut cfg = Self {
+			nodes: Vec::new(),
+			edges: Vec::new(),
+			blocks: Vec::new(),
+		};
+		cfg.add(Node::Entry);
+		cfg.add(Node::Return);
+		cfg.add(Node::FallOff);
+		let ends = match &body.as_ref().kind {
+			ExprKind::Block(stmts) => cfg.add_block(stmts, vec![ENTRY], RETURN),
+			// A body that isn't a block is the value
+			_ => {
+				if body.as_ref().typ != TypeData::Never {
+					cfg.connect(ENTRY, RETURN);
+				}
+				Vec::new()
+			}
+		};
+		for end in ends {
+			cfg.connect(end, FALL_OFF);
+		}
+		cfg
+	}
+
+	/// Adds the statements of a block after `preds`, the returned values go to `ret`.
+	/// Gives the nodes that continue after the block
+	fn add_block(&mut self, stmts: &[Span<hir::Expr>], mut preds: Vec<NodeId>, ret: NodeId) -> Vec<NodeId> {
+		let mut block = Vec::new();
+		for stmt in stmts {
+			let node = self.add(Node::Stmt(Span::new((), *stmt.loc())));
+			block.push(node);
+			for pred in preds.drain(..) {
+				self.connect(pred, node);
+			}
+			let (value, returns) = match &stmt.as_ref().kind {
+				ExprKind::Return(value) => (value.as_ref(), true),
+				_ => (stmt, false),
+			};
+			let mut ends = match &value.as_ref().kind {
+				// A nested block gives its value to the statement it's in
+				ExprKind::Block(inner) => {
+					let join = self.add(Node::Join);
+					let ends = self.add_block(inner, vec![node], join);
+					for end in ends {
+						self.connect(end, join);
+					}
+					vec![join]
+				}
+				_ => vec![node],
+			};
+			if value.as_ref().typ == TypeData::Never {
+				ends.clear();
+			}
+			if returns {
+				for end in ends {
+					self.connect(end, ret);
+				}
+			} else {
+				preds = ends;
+			}
+		}
+		self.blocks.push(block);
+		preds
+	}
+
+	fn add(&mut self, node: Node) -> NodeId {
+		self.nodes.push(node);
+		self.edges.push(Vec::new());
+		NodeId(self.nodes.len() - 1)
+	}
+
+	fn connect(&mut self, from: NodeId, to: NodeId) {
+		self.edges[from.0].push(to);
+	}
+
+	fn reachable(&self) -> Vec<bool> {
+		let mut reached = vec![false; self.nodes.len()];
+		let mut stack = vec![ENTRY];
+		while let Some(node) = stack.pop() {
+			if !reached[node.0] {
+				reached[node.0] = true;
+				stack.extend(&self.edges[node.0]);
+			}
+		}
+		reached
+	}
+
+	/// Whether the end of the body can be reached without giving a value
+	pub fn falls_off(&self) -> bool {
+		self.reachable()[FALL_OFF.0]
+	}
+
+	/// Locations of the statements that can't be reached, each one goes
+	/// from the first unreachable statement of a block to the end of the block
+	pub fn unreachable(&self) -> Vec<Location> {
+		let reached = self.reachable();
+		let mut ret = Vec::new();
+		for block in &self.blocks {
+			// A block that can't be reached at all is reported by the block it's in
+			let first = (1..block.len()).find(|&i| reached[block[i - 1].0] && !reached[block[i].0]);
+			if let Some(first) = first {
+				let stmts = block[first..]
+					.iter()
+					.filter_map(|n| match &self.nodes[n.0] {
+						Node::Stmt(s) => Some(s.clone()),
+						_ => None,
+					})
+					.collect::<Vec<Span<()>>>();
+				ret.push(*Span::join(&stmts, ()).loc());
+			}
+		}
+		ret
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ast::Value;
+	use crate::source_map::{FileId, SourceMap};
+	use crate::span::location::FilePosition;
+
+	fn file() -> FileId {
+		SourceMap::new().add("test.lang".into(), String::new())
+	}
+
+	/// The whole line `line`, every line is 10 bytes long
+	fn line(file: FileId, line: usize) -> Location {
+		let start = (line - 1) * 10;
+		Location::new(file, FilePosition::new(line, 1, start), FilePosition::new(line, 9, start + 9))
+	}
+
+	fn expr(kind: ExprKind, typ: TypeData, loc: Location) -> Span<hir::Expr> {
+		Span::new(hir::Expr { kind, typ }, loc)
+	}
+
+	/// A statement giving a number
+	fn num(loc: Location) -> Span<hir::Expr> {
+		expr(ExprKind::Value(Value::Num(1.0)), TypeData::Number, loc)
+	}
+
+	/// A call to a function that never returns
+	fn never(loc: Location) -> Span<hir::Expr> {
+		let f = expr(ExprKind::Fn("exit".into()), TypeData::Never, loc);
+		expr(ExprKind::Call(Box::new(f), Vec::new()), TypeData::Never, loc)
+	}
+
+	fn ret(value: Span<hir::Expr>) -> Span<hir::Expr> {
+		let loc = *value.loc();
+		expr(ExprKind::Return(Box::new(value)), TypeData::Never, loc)
+	}
+
+	fn block(stmts: Vec<Span<hir::Expr>>, typ: TypeData) -> Span<hir::Expr> {
+		let loc = *Span::join(&stmts, ()).loc();
+		expr(ExprKind::Block(stmts), typ, loc)
+	}
+
+	#[test]
+	fn nested_block_values() {
+		let f = file();
+		// { return { return 1 } }
+		let returned = block(vec![ret(block(vec![ret(num(line(f, 2)))], TypeData::Number))], TypeData::Number);
+		let cfg = Cfg::build(&returned);
+		assert!(!cfg.falls_off());
+		assert!(cfg.unreachable().is_empty());
+		// { { return 1 } }, the value of the inner block is discarded
+		let discarded = block(vec![block(vec![ret(num(line(f, 2)))], TypeData::Number)], TypeData::Void);
+		assert!(Cfg::build(&discarded).falls_off());
+	}
+
+	#[test]
+	fn never_call_mid_block() {
+		let f = file();
+		let body = block(vec![never(line(f, 1)), num(line(f, 2)), ret(num(line(f, 3)))], TypeData::Never);
+		let cfg = Cfg::build(&body);
+		assert!(!cfg.falls_off());
+		let span = Location::new(f, line(f, 2).start, line(f, 3).end);
+		assert_eq!(cfg.unreachable(), vec![span]);
+	}
+
+	#[test]
+	fn body_not_a_block() {
+		let f = file();
+		let cfg = Cfg::build(&num(line(f, 1)));
+		assert!(!cfg.falls_off());
+		assert!(cfg.unreachable().is_empty());
+		let cfg = Cfg::build(&never(line(f, 1)));
+		assert!(!cfg.falls_off());
+		assert!(cfg.unreachable().is_empty());
+	}
+
+	#[test]
+	fn unreachable_statements_after_a_return() {
+		let f = file();
+		// The statements after the return of the nested block are reported, and the ones after the block
+		let inner = block(vec![ret(num(line(f, 2))), num(line(f, 3))], TypeData::Number);
+		let body = block(vec![num(line(f, 1)), ret(inner), num(line(f, 4)), num(line(f, 5)), num(line(f, 6))], TypeData::Number);
+		let cfg = Cfg::build(&body);
+		assert!(!cfg.falls_off());
+		let mut unreachable = cfg.unreachable();
+		unreachable.sort_by_key(|l| l.start);
+		assert_eq!(unreachable, vec![line(f, 3), Location::new(f, line(f, 4).start, line(f, 6).end)]);
+	}
+}
@@ -1,110 +1,241 @@
-use crate::ast::{Expr, FnSignature, HasType, Ident, Item, Trait, TypeData, TypeError};
+use crate::ast::{Expr, FnSignature, HasType, Ident, Item, Trait, TypeData, TypeError, PRIMITIVE_TYPES};
+use crate::cfg::Cfg;
 use crate::error::Error;
 use crate::error::ReturnValue;
 use crate::file_provider::fs::FileProvider;
+use crate::hir::{self, ExprKind, MethodRef};
+use crate::lint::{self, LintLevels};
+use crate::resolver::{Resolver, Symbol, SymbolId, SymbolKind};
 use crate::scope::{self, Scope, TypeDB};
-use crate::span::{Span, SpanError};
+use crate::source_map::{FileId, ReadError, SourceMap};
+use crate::span::location::{FilePosition, Location};
+use crate::span::{HasLoc, Span, SpanError};
 
 use std::collections::HashMap;
 use std::path::Path;
 
 pub type TraitDB = HashMap<Ident, Trait>;
 
-pub fn check(items_slice: &[Span<Item>], type_db: &mut TypeDB, library: bool) -> Result<(), Error> {
+/// What is found while checking the items
+#[derive(Default)]
+pub struct CheckResults {
+	pub errors: Vec<Error>,
+	/// The checked functions and methods
+	pub program: hir::Program,
+	/// Names of the functions used, they are checked after the function using them
+	called: Vec<Ident>,
+	resolver: Resolver,
+	/// Types of the arguments and variables
+	local_types: HashMap<SymbolId, scope::Type>,
+	/// Arguments of the function being checked
+	fn_args: Vec<SymbolId>,
+	lints: LintLevels,
+}
+
+/// Checks the program in `file` with the standard library in `std_files`,
+/// the warnings found are given with it when there are no errors
+pub fn check(
+	file: FileId,
+	std_files: &[FileId],
+	items_slice: &[Span<Item>],
+	type_db: &mut TypeDB,
+	source_map: &SourceMap,
+	library: bool,
+	lints: LintLevels,
+) -> Result<(hir::Program, Vec<Error>), Vec<Error>> {
 	let mut trait_db = TraitDB::new();
-	let mut scope = match load_scope(type_db, &mut trait_db) {
+	let mut res = CheckResults::default();
+	let mut scope = match load_scope(type_db, &mut trait_db, &mut res, source_map, std_files) {
 		Ok(v) => v,
-		Err(e) => return Err(e),
+		Err(e) => return Err(vec![e]),
 	};
-	load_items(items_slice, &mut scope, type_db, &mut trait_db)?;
+	// The standard library is checked with the default levels
+	res.lints = lints;
+	for item in items_slice {
+		let unknown = res.lints.add_item(item.loc(), item.as_ref().attributes());
+		res.errors.extend(unknown);
+	}
+	load_items(items_slice, &mut scope, type_db, &mut trait_db, &mut res);
 	if !library {
 		println!("Loaded items, checking main");
 		if let Ok(TypeData::Fn(FnSignature(args, ret))) = scope
 			.get_type(&String::from("main"))
-			.map(|x| x.unwrap_ref())
+			.map(|x| x.unwrap_ref().clone())
 		{
-			if args.is_empty() {
-				if ret.as_ref().as_ref() == &TypeData::Void {
-					// println!("main found");
-					let res = check_item(&String::from("main"), &mut scope, type_db).unwrap();
-					// println!(
-					// 	"{}",
-					// 	scope.map(&|x| {
-					// 		if let Some((item, checked)) = x {
-					// 			format!("([{}] {})", checked, item)
-					// 		} else {
-					// 			format!("NONE")
-					// 		}
-					// 	})
-					// );
-					res
-				} else {
-					return Err(ret.error(
-						"Return type should be void for main",
-						ReturnValue::MainNonVoidRetType,
-					));
-				}
-			} else {
+			if !args.is_empty() {
 				let spans = args
 					.iter()
 					.map(|(_, x)| x.clone())
 					.collect::<Vec<Span<TypeData>>>();
-				return Err(Span::join(&spans, ()).error(
+				res.errors.push(Span::join(&spans, ()).error(
 					"Main fubction shouldn't have arguments",
 					ReturnValue::MainHasArguments,
 				));
 			}
+			if ret.as_ref().as_ref() != &TypeData::Void {
+				res.errors.push(ret.error(
+					"Return type should be void for main",
+					ReturnValue::MainNonVoidRetType,
+				));
+			}
 		} else {
-			return Err(items_slice
-				.first()
-				.unwrap()
-				.error("No main function in non library", ReturnValue::NoMain));
+			let start = FilePosition::new(1, 1, 0);
+			let loc = items_slice.first().map_or(Location::new(file, start, start), |item| *item.loc());
+			res.errors.push(Span::new((), loc).error("No main function in non library", ReturnValue::NoMain));
 		}
-	}else{
-		for item in items_slice {
-			if let Item::Fn(a, _, _, _) = item.as_ref() {
-				println!("Checking: {}", a.as_ref());
-				check_item(a.as_ref(), &mut scope, type_db).unwrap()?;
-			}
+	}
+	for item in items_slice {
+		if let Item::Fn(a, _, _, _, _) = item.as_ref() {
+			println!("Checking: {}", a.as_ref());
+			check_item(a.as_ref(), &mut scope, type_db, &mut res);
 		}
-		Ok(())
 	}
-	
+	check_called(&mut scope, type_db, &mut res);
+	warn_unused(items_slice, library, &mut res);
+	lint_names(&mut res);
+	res.errors = res.lints.apply_deny_warnings(res.errors);
+	if res.errors.iter().any(Error::is_error) {
+		Err(res.errors)
+	} else {
+		res.program.symbols = res.resolver.into_table();
+		Ok((res.program, res.errors))
+	}
 }
 
 pub fn load_scope(
 	type_db: &mut TypeDB,
 	trait_db: &mut TraitDB,
+	res: &mut CheckResults,
+	source_map: &SourceMap,
+	std_files: &[FileId],
 ) -> Result<Scope<Option<(Span<Item>, bool)>>, Error> {
 	let mut scope = Scope::root();
-	if let Err(e) = load_std(&mut scope, type_db, trait_db, "") {
+	if let Err(e) = load_std(&mut scope, type_db, trait_db, res, source_map, std_files) {
 		return Err(e);
 	}
 	Ok(scope)
 }
 
+/// Checks the functions used by the checked code that haven't been checked yet
+pub fn check_called(
+	scope: &mut Scope<Option<(Span<Item>, bool)>>,
+	type_db: &mut TypeDB,
+	res: &mut CheckResults,
+) {
+	while let Some(f) = res.called.pop() {
+		check_item(&f, scope, type_db, res);
+	}
+}
+
+/// Warns about the arguments, variables and traits never used, and in a program about the functions not used by `main`.
+/// Names starting with `_` and the `self` parameters are never reported
+fn warn_unused(items: &[Span<Item>], library: bool, res: &mut CheckResults) {
+	let table = res.resolver.table();
+	let used = table.used();
+	for (id, symbol) in table.iter() {
+		// A method takes `self` even when it doesn't need it, as the trait says so
+		let is_self = symbol.kind == SymbolKind::Arg && symbol.name == "self";
+		if used.contains(&id) || symbol.name.starts_with('_') || is_self {
+			continue;
+		}
+		let (lint, what) = match symbol.kind {
+			SymbolKind::Arg => (&lint::UNUSED_VARIABLES, "Unused parameter"),
+			SymbolKind::Local | SymbolKind::LocalMut => (&lint::UNUSED_VARIABLES, "Unused variable"),
+			SymbolKind::Trait => (&lint::DEAD_CODE, "Unused trait"),
+			// Using a function only counts when it's checked, so they're found below
+			SymbolKind::Fn => continue,
+		};
+		let info = format!("{} `{}`", what, symbol.name);
+		res.errors.extend(res.lints.report(lint, &symbol.def, info));
+	}
+	if library {
+		return;
+	}
+	let reachable = res.program.reachable_fns("main");
+	for item in items {
+		if let Item::Fn(name, _, _, _, _) = item.as_ref() {
+			let name_str = name.as_ref();
+			if name_str != "main" && !name_str.starts_with('_') && !reachable.contains(name_str) {
+				let info = format!("Unused function `{}`", name_str);
+				res.errors.extend(res.lints.report(&lint::DEAD_CODE, name.loc(), info));
+			}
+		}
+	}
+}
+
+/// Warns about the names that don't follow the naming conventions, and the ones that hide another one
+fn lint_names(res: &mut CheckResults) {
+	let table = res.resolver.table();
+	for (_, symbol) in table.iter() {
+		let name = &symbol.name;
+		let (lint, info) = match symbol.kind {
+			SymbolKind::Trait if !lint::is_camel_case(name) => (
+				&lint::NON_CAMEL_CASE_TRAITS,
+				format!("Trait `{}` should have a camel case name such as `{}`", name, lint::to_camel_case(name)),
+			),
+			SymbolKind::Trait => continue,
+			_ if !lint::is_snake_case(name) => {
+				let what = match symbol.kind {
+					SymbolKind::Fn => "Function",
+					SymbolKind::Arg => "Parameter",
+					_ => "Variable",
+				};
+				(
+					&lint::NON_SNAKE_CASE,
+					format!("{} `{}` should have a snake case name such as `{}`", what, name, lint::to_snake_case(name)),
+				)
+			}
+			_ => continue,
+		};
+		res.errors.extend(res.lints.report(lint, &symbol.def, info));
+	}
+	for (_, symbol) in table.shadowing() {
+		let shadowed = table.get(symbol.shadows.unwrap());
+		let what = match shadowed.kind {
+			SymbolKind::Fn => "function",
+			SymbolKind::Arg => "parameter",
+			SymbolKind::Local | SymbolKind::LocalMut => "variable",
+			SymbolKind::Trait => unreachable!("Traits are named apart from the other bindings"),
+		};
+		let info = format!("`{}` shadows the {} `{}`", symbol.name, what, shadowed.name);
+		let e = res.lints.report(&lint::SHADOWING, &symbol.def, info);
+		res.errors.extend(e.map(|e| e.secondary_label(shadowed.def, format!("The {} is defined here", what))));
+	}
+}
+
+/// Checks an expression written outside of a function, like the lines of the repl.
+/// The variables it defines are kept for the next ones
+#[allow(unused)]
+pub fn check_top_level_expr(
+	expr: &Span<Expr>,
+	scope: &mut Scope<Option<(Span<Item>, bool)>>,
+	type_db: &mut TypeDB,
+	res: &mut CheckResults,
+) -> Span<hir::Expr> {
+	res.resolver.resolve_expr(expr, &mut res.errors);
+	let expr = check_expr(expr, None, scope, type_db, res);
+	check_called(scope, type_db, res);
+	expr
+}
+
+/// Checks the function `variable` if it hasn't been checked yet
 fn check_item(
 	variable: &String,
 	scope: &mut Scope<Option<(Span<Item>, bool)>>,
 	type_db: &mut TypeDB,
-) -> Option<Result<(), Error>> {
+	res: &mut CheckResults,
+) {
 	if let Ok(Some((main, checked))) = scope.get_value(variable) {
-		if *checked {
-			Some(Ok(()))
-		} else {
+		if !*checked {
 			let main = main.clone();
 			scope
 				.set_value(variable, Some((main.clone(), true)))
 				.expect("Item cant be checked off");
-			match main.val() {
-				Item::Fn(_, args, ret, block) => Some(check_fn(scope, type_db, args, ret, block)),
-
-				_ => None,
+			if let Item::Fn(_, args, ret, block, _) = main.val() {
+				let f = check_fn(scope, type_db, args, ret, block, res);
+				res.program.fns.insert(variable.clone(), f);
 			}
 		}
-	} else {
-		//unreachable!("{} is not defined or not an item", variable)
-		None
 	}
 }
 
@@ -114,199 +245,593 @@ pub fn check_fn(
 	args: Vec<(Span<Ident>, Span<TypeData>)>,
 	ret: Span<TypeData>,
 	block: Span<Expr>,
-) -> Result<(), Error> {
-	*scope = scope.clone().push();
-	for (arg_name, arg_type) in args {
-		if let Err(_) = scope.add_variable(arg_name.val(), scope::Type::NoMut(arg_type.val()), None)
-		{
-			return Err(
-				arg_name.error("Name already defined", ReturnValue::NameDefined)
-			);
-		}
-	}
-	let ret_block = match block.get_type(&scope, type_db) {
-		Ok(v) => v,
-		Err(e) => panic!("{:?}", e.as_ref()),
-	};
-	if ret.as_ref() != &ret_block {
-		return Err(block.error(
-			format!("Return type doesn't match defined return type (`{}`)", ret),
-			ReturnValue::TypesDontMatch,
-		));
+	res: &mut CheckResults,
+) -> hir::Fn {
+	let arg_ids = res.resolver.resolve_fn(&args, &block, &mut res.errors);
+	for ((_, arg_type), id) in args.iter().zip(&arg_ids) {
+		res.local_types.insert(*id, scope::Type::NoMut(arg_type.val()));
 	}
+	let outer_args = std::mem::replace(&mut res.fn_args, arg_ids.clone());
 	// println!("Check item's block");
-	let res = check_expr(&block, scope, type_db);
-	*scope = scope.clone().pop();
-	res
+	// The value of the body is checked by its returns, and the CFG finds the paths without one
+	let (kind, typ) = lower_expr(&block, Some(&ret), scope, type_db, res);
+	let body = Span::new(hir::Expr { kind, typ }, *block.loc());
+	res.fn_args = outer_args;
+	let cfg = Cfg::build(&body);
+	for loc in cfg.unreachable() {
+		res.errors.extend(res.lints.report(&lint::UNREACHABLE_CODE, &loc, "Unreachable code"));
+	}
+	if cfg.falls_off() && ret.as_ref() != &TypeData::Void {
+		res.errors.push(ret.error("Not all paths return a value", ReturnValue::MissingReturn));
+	}
+	hir::Fn {
+		args: arg_ids.into_iter().zip(args).map(|(id, (_, typ))| (id, typ.val())).collect(),
+		body,
+	}
 }
 
+/// Checks `expr` and all the expressions in it, and that its type is `expected` when it's known,
+/// the location of `expected` is where that type is written.
+/// Returns the typed expression, its type is `Err` when it can't be known because of an error
 pub fn check_expr(
 	expr: &Span<Expr>,
+	expected: Option<&Span<TypeData>>,
 	scope: &mut Scope<Option<(Span<Item>, bool)>>,
 	type_db: &mut TypeDB,
-) -> Result<(), Error> {
+	res: &mut CheckResults,
+) -> Span<hir::Expr> {
 	// println!("Checking expr: {}", expr);
+	let (kind, typ) = lower_expr(expr, expected, scope, type_db, res);
+	if let Some(expected) = expected.filter(|t| typ != TypeData::Err && typ != TypeData::Never && &typ != t.as_ref()) {
+		let e = match (expr.as_ref(), &kind) {
+			// Returns and blocks check the returned value instead
+			(Expr::Return(_), _) => None,
+			(_, ExprKind::Block(stmts)) if !stmts.iter().any(|s| matches!(s.as_ref().kind, ExprKind::Return(_))) => {
+				Some(expr.error(
+					format!("Expected `{}`, but the block doesn't return a value", expected),
+					ReturnValue::TypesDontMatch,
+				))
+			}
+			(_, ExprKind::Block(_)) => None,
+			_ => Some(
+				expr.error(format!("Expected `{}`, found `{}`", expected, typ), ReturnValue::TypesDontMatch)
+					.label(format!("This is `{}`", typ)),
+			),
+		};
+		if let Some(e) = e {
+			res.errors.push(e.secondary_label(*expected.loc(), "Expected because of this type"));
+		}
+	}
+	Span::new(hir::Expr { kind, typ }, *expr.loc())
+}
+
+/// The symbol of the function called by `callee`, to point to its definition when the call is wrong
+fn callee_symbol<'a>(callee: &Span<hir::Expr>, res: &'a CheckResults) -> Option<&'a Symbol> {
+	let table = res.resolver.table();
+	Some(table.get(table.reference(callee.loc())?))
+}
+
+/// Symbol defined by the `Define` or `DefineMut` expression
+fn defined_symbol(expr: &Span<Expr>, res: &CheckResults) -> SymbolId {
+	res.resolver
+		.table()
+		.definition(expr.loc())
+		.expect("Definitions are resolved before being checked")
+}
+
+/// An expression that couldn't be checked
+fn error_expr() -> (ExprKind, TypeData) {
+	(ExprKind::None, TypeData::Err)
+}
+
+/// An expression with an operand that never gives a value, the operands are run in order until that one
+fn diverging_expr(operands: Vec<Span<hir::Expr>>) -> (ExprKind, TypeData) {
+	(ExprKind::Block(operands), TypeData::Never)
+}
+
+fn is_never(expr: &Span<hir::Expr>) -> bool {
+	expr.as_ref().typ == TypeData::Never
+}
+
+fn lower_expr(
+	expr: &Span<Expr>,
+	expected: Option<&Span<TypeData>>,
+	scope: &mut Scope<Option<(Span<Item>, bool)>>,
+	type_db: &mut TypeDB,
+	res: &mut CheckResults,
+) -> (ExprKind, TypeData) {
 	match expr.as_ref() {
 		Expr::Block(b) => {
-			*scope = scope.clone().push();
-			let mut reversed_block = b.clone();
-			reversed_block.reverse();
-			for e in &reversed_block {
-				if let Err(e) = check_expr(e, scope, type_db) {
-					return Err(e);
+			let mut ret = None;
+			let mut diverges = false;
+			let mut body = Vec::new();
+			for e in b {
+				if let Expr::Return(_) = e.as_ref() {
+					let e = check_expr(e, expected, scope, type_db, res);
+					ret.get_or_insert(e.as_ref().typ.clone());
+					body.push(e);
+				} else {
+					body.push(check_expr(e, None, scope, type_db, res));
 				}
+				diverges |= body.last().unwrap().as_ref().typ == TypeData::Never;
 			}
-			*scope = scope.clone().pop();
-			Ok(())
-		}
-		Expr::Define(id, expr) => {
-			let typ = match expr.get_type(scope, type_db) {
-				Ok(v) => v,
-				Err(t) => return Err(get_type_error(t)),
+			let typ = match ret {
+				Some(t) => t,
+				None if diverges => TypeData::Never,
+				None => TypeData::Void,
 			};
-			scope
-				.add_variable(id.clone(), scope::Type::NoMut(typ), None)
-				.expect("Error adding variable");
-			Ok(())
+			(ExprKind::Block(body), typ)
 		}
-		Expr::DefineMut(id, expr) => {
-			let typ = match expr.get_type(scope, type_db) {
-				Ok(v) => v,
-				Err(t) => return Err(get_type_error(t)),
-			};
-			scope
-				.add_variable(id.clone(), scope::Type::Mut(typ), None)
-				.expect("Error adding variable");
-			Ok(())
+		Expr::Return(e) => {
+			let e = check_expr(e, expected, scope, type_db, res);
+			let typ = e.as_ref().typ.clone();
+			(ExprKind::Return(Box::new(e)), typ)
+		}
+		Expr::Define(_, value) => {
+			let value = check_expr(value, None, scope, type_db, res);
+			let id = defined_symbol(expr, res);
+			res.local_types.insert(id, scope::Type::NoMut(value.as_ref().typ.clone()));
+			(ExprKind::Define(id, Box::new(value)), TypeData::Void)
+		}
+		Expr::DefineMut(_, value) => {
+			let value = check_expr(value, None, scope, type_db, res);
+			let id = defined_symbol(expr, res);
+			res.local_types.insert(id, scope::Type::Mut(value.as_ref().typ.clone()));
+			(ExprKind::DefineMut(id, Box::new(value)), TypeData::Void)
 		}
-		_ => {
-			match expr.get_type_with_call_cb(&scope.clone(), &mut type_db.clone(), &mut |e| {
-				if let Expr::Ident(id) = e.as_ref() {
-					// println!("ID: {}", id);
-					if let Some(Err(e)) = check_item(id, scope, type_db) {
-						return Err(e);
+		Expr::Value(v) => match v.get_type(scope, type_db) {
+			Ok(t) => (ExprKind::Value(v.clone()), t),
+			Err(t) => {
+				res.errors.push(get_type_error(t, type_db));
+				error_expr()
+			}
+		},
+		Expr::CompilerIntrinsic(i) => match i.get_type(scope, type_db) {
+			Ok(t) => (ExprKind::CompilerIntrinsic(*i, res.fn_args.clone()), t),
+			Err(t) => {
+				res.errors.push(get_type_error(t, type_db));
+				error_expr()
+			}
+		},
+		Expr::Ident(id) => match res.resolver.table().reference(expr.loc()) {
+			Some(symbol) if res.resolver.table().get(symbol).kind == SymbolKind::Fn => {
+				res.called.push(id.clone());
+				let typ = scope.get_type(id).expect("Functions are in the scope before being used");
+				(ExprKind::Fn(id.clone()), typ.clone().unwrap())
+			}
+			Some(symbol) => (ExprKind::Local(symbol), res.local_types[&symbol].clone().unwrap()),
+			// Already reported by the resolver
+			None => error_expr(),
+		},
+		Expr::Call(callee, args) => {
+			let callee = check_expr(callee, None, scope, type_db, res);
+			match callee.as_ref().typ.clone() {
+				TypeData::Fn(FnSignature(params, ret)) => {
+					if params.len() != args.len() {
+						let mut e = expr.error(
+							format!(
+								"This function takes {} argument{} but {} {} supplied",
+								params.len(),
+								if params.len() == 1 { "" } else { "s" },
+								args.len(),
+								if args.len() == 1 { "was" } else { "were" }
+							),
+							ReturnValue::WrongArgumentCount,
+						);
+						if let Some(symbol) = callee_symbol(&callee, res) {
+							e = e.secondary_label(symbol.def, format!("`{}` is defined here", symbol.name));
+						}
+						res.errors.push(e);
+					}
+					let args = args
+						.iter()
+						.enumerate()
+						.map(|(i, arg)| check_expr(arg, params.get(i).map(|(_, t)| t), scope, type_db, res))
+						.collect::<Vec<Span<hir::Expr>>>();
+					let typ = if args.iter().any(is_never) { TypeData::Never } else { ret.val() };
+					(ExprKind::Call(Box::new(callee), args), typ)
+				}
+				TypeData::Never => {
+					let mut operands = vec![callee];
+					for arg in args {
+						operands.push(check_expr(arg, None, scope, type_db, res));
+					}
+					diverging_expr(operands)
+				}
+				TypeData::Err => {
+					for arg in args {
+						check_expr(arg, None, scope, type_db, res);
+					}
+					error_expr()
+				}
+				callee_type => {
+					let mut call_args = vec![callee];
+					for arg in args {
+						call_args.push(check_expr(arg, None, scope, type_db, res));
+					}
+					let args_types = call_args[1..].iter().map(|x| x.as_ref().typ.clone()).collect::<Vec<TypeData>>();
+					if args_types.contains(&TypeData::Err) {
+						return error_expr();
+					}
+					let args_tuple = TypeData::Tuple(args_types).default_type();
+					if let Some(trait_impl) = type_db.get(&callee_type).get_impl_trait("Call", &[&args_tuple]) {
+						let method = MethodRef {
+							for_type: type_db.get(&callee_type).type_data().clone(),
+							trait_name: "Call".into(),
+							defining_types: trait_impl.defining_types(),
+							method: "call".into(),
+						};
+						let typ = trait_impl.get_typedef("Output").unwrap().type_data().clone();
+						return (ExprKind::MethodCall(method, call_args), typ);
 					}
+					res.errors.push(get_type_error(Span::new(
+						TypeError::TraitNotImplemented("Call".into(), vec![args_tuple], callee_type),
+						*call_args[0].loc(),
+					), type_db));
+					error_expr()
+				}
+			}
+		}
+		Expr::Cast(value, typ) => {
+			let value = check_expr(value, None, scope, type_db, res);
+			let value_type = value.as_ref().typ.clone();
+			if value_type == TypeData::Err {
+				error_expr()
+			} else if value_type == TypeData::Never {
+				diverging_expr(vec![value])
+			} else if value_type.can_cast_to(typ.as_ref()) {
+				if &value_type == typ.as_ref() {
+					let info = format!("Casting `{}` as `{}` does nothing, the value already has that type", value_type, typ);
+					let e = res.lints.report(&lint::TRIVIAL_CASTS, expr.loc(), info);
+					res.errors.extend(e.map(|e| e.help(format!("Remove the `as {}`", typ))));
+				}
+				(ExprKind::Cast(Box::new(value), typ.val()), typ.val())
+			} else {
+				res.errors.push(get_type_error(Span::new(
+					TypeError::InvalidCast(value_type, typ.val()),
+					*expr.loc(),
+				), type_db));
+				error_expr()
+			}
+		}
+		Expr::Display(value) => unary_op("Display", "to_string", value, value.loc(), scope, type_db, res),
+		Expr::Neg(value) => unary_op("Neg", "neg", value, expr.loc(), scope, type_db, res),
+		Expr::Add(lhs, rhs) => binary_op("Add", "add", lhs, rhs, scope, type_db, res),
+		Expr::Sub(lhs, rhs) => binary_op("Sub", "sub", lhs, rhs, scope, type_db, res),
+		Expr::Mul(lhs, rhs) => binary_op("Mul", "mul", lhs, rhs, scope, type_db, res),
+		Expr::Div(lhs, rhs) => binary_op("Div", "div", lhs, rhs, scope, type_db, res),
+		Expr::Exp(lhs, rhs) => binary_op("Exp", "exp", lhs, rhs, scope, type_db, res),
+		Expr::Eq(lhs, rhs) => {
+			let (kind, typ) = binary_op("Eq", "eq", lhs, rhs, scope, type_db, res);
+			if let ExprKind::MethodCall(method, _) = &kind {
+				if method.for_type == TypeData::Number {
+					let info = "Numbers compared with `==`, rounding can make equal numbers differ";
+					res.errors.extend(res.lints.report(&lint::FLOAT_EQUALITY, expr.loc(), info));
 				}
-				Ok(())
-			}) {
-				Ok(_) => Ok(()),
-				Err(t) => Err(get_type_error(t)),
 			}
+			(kind, typ)
 		}
+		Expr::Lt(lhs, rhs) => binary_op("PartialOrd", "lt", lhs, rhs, scope, type_db, res),
+		Expr::Le(lhs, rhs) => binary_op("PartialOrd", "le", lhs, rhs, scope, type_db, res),
+		Expr::Gt(lhs, rhs) => binary_op("PartialOrd", "gt", lhs, rhs, scope, type_db, res),
+		Expr::Ge(lhs, rhs) => binary_op("PartialOrd", "ge", lhs, rhs, scope, type_db, res),
+		Expr::None => (ExprKind::None, TypeData::Void),
+	}
+}
+
+/// A unary operator, calls `method` of the trait `trait_name` implemented by the type of `value`.
+/// `Display` gives a `string`, `Neg` has the `Output` type of the trait. Errors are reported at `loc`
+fn unary_op(
+	trait_name: &str,
+	method: &str,
+	value: &Span<Expr>,
+	loc: &Location,
+	scope: &mut Scope<Option<(Span<Item>, bool)>>,
+	type_db: &mut TypeDB,
+	res: &mut CheckResults,
+) -> (ExprKind, TypeData) {
+	let value = check_expr(value, None, scope, type_db, res);
+	let value_type = value.as_ref().typ.clone();
+	if value_type == TypeData::Err {
+		return error_expr();
+	}
+	if value_type == TypeData::Never {
+		return diverging_expr(vec![value]);
+	}
+	let for_type = type_db.get(&value_type);
+	if let Some(trait_impl) = for_type.get_impl_trait(trait_name, &[]) {
+		let typ = match trait_name {
+			"Display" => TypeData::String,
+			_ => trait_impl.get_typedef("Output").unwrap().type_data().clone(),
+		};
+		let method = MethodRef {
+			for_type: for_type.type_data().clone(),
+			trait_name: trait_name.into(),
+			defining_types: Vec::new(),
+			method: method.into(),
+		};
+		return (ExprKind::MethodCall(method, vec![value]), typ);
+	}
+	res.errors.push(get_type_error(Span::new(
+		TypeError::TraitNotImplemented(trait_name.into(), vec![], value_type),
+		*loc,
+	), type_db));
+	error_expr()
+}
+
+/// A binary operator, calls `method` of the trait `trait_name` implemented by the type of `lhs` for the type of `rhs`.
+/// Comparisons are `bool`, the other operators have the `Output` type of the trait
+fn binary_op(
+	trait_name: &str,
+	method: &str,
+	lhs: &Span<Expr>,
+	rhs: &Span<Expr>,
+	scope: &mut Scope<Option<(Span<Item>, bool)>>,
+	type_db: &mut TypeDB,
+	res: &mut CheckResults,
+) -> (ExprKind, TypeData) {
+	let lhs = check_expr(lhs, None, scope, type_db, res);
+	let rhs = check_expr(rhs, None, scope, type_db, res);
+	let lhs_type = lhs.as_ref().typ.clone();
+	if lhs_type == TypeData::Err || rhs.as_ref().typ == TypeData::Err {
+		return error_expr();
+	}
+	if is_never(&lhs) || is_never(&rhs) {
+		return diverging_expr(vec![lhs, rhs]);
+	}
+	let rhs_type = type_db.get(&rhs.as_ref().typ);
+	let for_type = type_db.get(&lhs_type);
+	if let Some(trait_impl) = for_type.get_impl_trait(trait_name, &[&rhs_type]) {
+		let typ = match trait_name {
+			"Eq" | "PartialOrd" => TypeData::Bool,
+			_ => trait_impl.get_typedef("Output").unwrap().type_data().clone(),
+		};
+		let method = MethodRef {
+			for_type: for_type.type_data().clone(),
+			trait_name: trait_name.into(),
+			defining_types: trait_impl.defining_types(),
+			method: method.into(),
+		};
+		return (ExprKind::MethodCall(method, vec![lhs, rhs]), typ);
 	}
+	res.errors.push(get_type_error(Span::new(
+		TypeError::TraitNotImplemented(trait_name.into(), vec![rhs_type], lhs_type),
+		*lhs.loc(),
+	), type_db));
+	error_expr()
 }
 
-fn get_type_error(t: Span<TypeError>) -> Error {
+fn get_type_error(t: Span<TypeError>, type_db: &TypeDB) -> Error {
 	match t.as_ref() {
-		TypeError::TraitNotImplemented(name, defining_types, for_type) => t.error(
-			format!(
-				"Trait `{}<{}>` not implemented for type `{}`",
-				name,
-				defining_types
-					.iter()
-					.map(|x| format!("{}", x))
-					.collect::<Vec<String>>()
-					.join(", "),
-				for_type
-			),
-			ReturnValue::TraitNotImplemented,
-		),
+		TypeError::TraitNotImplemented(name, defining_types, for_type) => {
+			let e = t.error(
+				format!(
+					"Trait `{}<{}>` not implemented for type `{}`",
+					name,
+					defining_types
+						.iter()
+						.map(|x| format!("{}", x))
+						.collect::<Vec<String>>()
+						.join(", "),
+					for_type
+				),
+				ReturnValue::TraitNotImplemented,
+			);
+			let mut impls = type_db
+				.implementations(name)
+				.into_iter()
+				.map(|(t, i)| format!("`{} for {}`", i, t.type_data()))
+				.collect::<Vec<String>>();
+			if impls.is_empty() {
+				return e;
+			}
+			impls.sort();
+			e.help(format!("These types implement `{}`: {}", name, impls.join(", ")))
+		}
 		// TypeError::BranchesDontMatch => {
 		// 	t.error("Branches dont match", ReturnValue::BrnchRetTypesDontMatch)
 		// }
-		TypeError::IdentNotFound(id) => t.error(
-			format!("Name `{}` not defined", id),
-			ReturnValue::IdentNotDefined,
-		),
-		TypeError::Err(e) => e.clone(),
+		TypeError::InvalidCast(from, to) => {
+			if from.is_primitive() && to.is_primitive() {
+				t.error(
+					format!("Can't cast `{}` as `{}`", from, to),
+					ReturnValue::InvalidCast,
+				)
+			} else {
+				t.error(
+					format!(
+						"Non-primitive cast `{}` as `{}`, only primitive types can be casted with `as`",
+						from, to
+					),
+					ReturnValue::InvalidCast,
+				)
+			}
+		}
 	}
 }
 
-fn load_std<P: AsRef<Path>>(
+/// Reads the files of the standard library, in the order they are loaded
+pub fn read_std<P: AsRef<Path>>(source_map: &mut SourceMap, std_path: P) -> Result<Vec<FileId>, ReadError> {
+	let fp = FileProvider::new(&std_path);
+	let files = [
+		"src/std/print.lang",
+		"src/std/ops.lang",
+		"src/std/bigint.lang",
+		"src/std/string.lang",
+	];
+	files.iter().map(|f| source_map.load(&fp, f)).collect()
+}
+
+fn load_std(
 	scope: &mut Scope<Option<(Span<Item>, bool)>>,
 	type_db: &mut TypeDB,
 	trait_db: &mut TraitDB,
-	std_path: P,
+	res: &mut CheckResults,
+	source_map: &SourceMap,
+	std_files: &[FileId],
 ) -> Result<(), Error> {
-	let fp = FileProvider::new(&std_path);
-	let files = ["src/std/print.lang", "src/std/ops.lang"];
-	for f in &files {
-		// println!("Loading file {}", f);
+	for &file in std_files {
 		// println!("Tokenizing");
-		let tokens = match crate::tokens::tokenize(f, &fp) {
+		let tokens = match crate::tokens::tokenize(file, source_map) {
 			Ok(v) => v,
 			Err(e) => return Err(e),
 		};
 		// println!("Parsing");
-		let items = match crate::parser::parse_lines(tokens, true) {
-			Ok(v) => v,
-			Err(e) => return Err(e),
-		};
+		let (items, mut errors) = crate::parser::parse_lines(tokens, true);
+		if !errors.is_empty() {
+			return Err(errors.remove(0));
+		}
 		// println!("Loading items into scope");
-		load_items(&items, scope, type_db, trait_db)?;
+		let errors_before = res.errors.len();
+		load_items(&items, scope, type_db, trait_db, res);
+		if let Some(i) = res.errors[errors_before..].iter().position(Error::is_error) {
+			return Err(res.errors.remove(errors_before + i));
+		}
+		// Warnings in the standard library aren't useful to the user
+		res.errors.truncate(errors_before);
 	}
 	Ok(())
 }
 
+/// Reports the types of the parameters and the return type that aren't defined: the ones that aren't primitive types
+/// or one of `names`, the types defined where the signature is
+fn check_signature_types(
+	args: &[(Span<Ident>, Span<TypeData>)],
+	ret: &Span<TypeData>,
+	names: &[&str],
+	res: &mut CheckResults,
+) {
+	for typ in args.iter().map(|(_, t)| t).chain(std::iter::once(ret)) {
+		if let Some(name) = undefined_type(typ.as_ref(), names) {
+			let e = typ.error(format!("Type `{}` not defined", name), ReturnValue::IdentNotDefined);
+			// The location is only the name's when it isn't inside another type, like `[T]`
+			res.errors.push(if let TypeData::Other(_) = typ.as_ref() {
+				e.suggest_similar(name, PRIMITIVE_TYPES.iter().chain(names).copied(), typ.loc())
+			} else {
+				e
+			});
+		}
+	}
+}
+
+/// The first name in `typ` that isn't a primitive type or one of `names`
+fn undefined_type<'a>(typ: &'a TypeData, names: &[&str]) -> Option<&'a str> {
+	match typ {
+		TypeData::Other(name) if !names.contains(&name.as_str()) => Some(name),
+		TypeData::Array(t) => undefined_type(t, names),
+		TypeData::Tuple(types) => types.iter().find_map(|t| undefined_type(t, names)),
+		TypeData::Fn(FnSignature(args, ret)) => args
+			.iter()
+			.map(|(_, t)| t.as_ref())
+			.chain(std::iter::once(ret.as_ref().as_ref()))
+			.find_map(|t| undefined_type(t, names)),
+		_ => None,
+	}
+}
+
+/// Adds the items to the scope, checking the implementations of traits
 pub fn load_items(
 	items: &[Span<Item>],
 	scope: &mut Scope<Option<(Span<Item>, bool)>>,
 	type_db: &mut TypeDB,
 	trait_db: &mut TraitDB,
-) -> Result<(), Error> {
+	res: &mut CheckResults,
+) {
+	// Functions are added first so the methods can use the ones defined after them
+	res.resolver.declare_items(items);
+	for item in items {
+		if let Item::Fn(name, args, ret, _, _) = item.as_ref() {
+			check_signature_types(args, ret, &[], res);
+			let fn_type = item.as_ref().get_type().unwrap();
+			if scope
+				.add_variable(name.val(), scope::Type::NoMut(fn_type.clone()), Some((item.clone(), false)))
+				.is_err()
+			{
+				let mut e = name
+					.error(format!("Name `{}` already defined", name.as_ref()), ReturnValue::NameDefined)
+					.label("Defined again here");
+				if let Ok(Some((first, _))) = scope.get_value(name.as_ref()) {
+					if let Item::Fn(first_name, _, _, _, _) = first.as_ref() {
+						e = e.secondary_label(*first_name.loc(), "First defined here");
+					}
+				}
+				res.errors.push(e);
+			}
+			// Functions with the same signature have the same type, so it can already be in the db
+			let _ = type_db.add(fn_type);
+		}
+	}
 	for item in items {
 		match item.as_ref() {
-			Item::Fn(name, _, _, _) => match scope.add_variable(
-				name.val(),
-				scope::Type::NoMut(item.as_ref().get_type().unwrap()),
-				Some((item.clone(), false)),
-			) {
-				Ok(v) => Ok(v),
-				Err(_) => Err("Error adding scope to variable".into()),
+			Item::Fn(_, _, _, _, _) => (),
+			Item::TraitDef(name, t, _) => {
+				if let Some(first) = trait_db.get(name.as_ref()) {
+					res.errors.push(
+						name.error(format!("Name `{}` already defined for a trait", name.as_ref()), ReturnValue::NameDefined)
+							.label("Defined again here")
+							.secondary_label(*first.name().loc(), "First defined here"),
+					);
+				} else {
+					let names = t.type_names().collect::<Vec<&str>>();
+					for FnSignature(args, ret) in t.methods().values() {
+						check_signature_types(args, ret, &names, res);
+					}
+					trait_db.insert(name.val(), t.clone());
+				}
 			}
-			.and_then(|_| match type_db.add(item.as_ref().get_type().unwrap()) {
-				Ok(v) => Ok(v),
-				Err(_) => Err(format!(
-					"Error adding type `{}` to db",
-					item.as_ref().get_type().unwrap()
-				)),
-			}),
-			Item::TraitDef(name, t) => match trait_db.insert(name.val(), t.clone()) {
-				Some(_) => Err(format!(
-					"Name `{}` already defined for a trait",
-					name.as_ref()
-				)),
-				None => Ok(()),
-			},
-			Item::ImplTrait(for_type_data, impl_trait) => {
+			Item::ImplTrait(for_type_data, impl_trait, _) => {
 				// println!("IMPL {} FOR {}", impl_trait, for_type_data);
 				*type_db = type_db.clone().push();
 				let for_type = type_db.get_or_add_to_root(for_type_data.as_ref());
+				let for_type_data_resolved = for_type.type_data().clone();
 				type_db.set(TypeData::SelfRef, for_type);
-				let res = if let Some(t) = trait_db.get(impl_trait.trait_name_string()) {
-					if impl_trait.matches_trait(t, &type_db, |type_db, args, ret, body|{
+				let trait_name = impl_trait.trait_name_span();
+				if let Some(t) = trait_db.get(impl_trait.trait_name_string()) {
+					let names = t.type_names().chain(impl_trait.typedef_names()).collect::<Vec<&str>>();
+					for (FnSignature(args, ret), _) in impl_trait.methods().values() {
+						check_signature_types(args, ret, &names, res);
+					}
+					if impl_trait.matches_trait(t, &type_db, |type_db, method, args, ret, body| {
 						let mut type_db = type_db.clone().push();
-						check_fn(scope, &mut type_db, args, ret, body)
-					})? {
+						let f = check_fn(scope, &mut type_db, args, ret, body, res);
+						let method = MethodRef {
+							for_type: for_type_data_resolved.clone(),
+							trait_name: trait_name.val(),
+							defining_types: impl_trait.defining_types(),
+							method: method.clone(),
+						};
+						res.program.methods.insert(method, f);
+					}) {
 						match type_db.get_mut(for_type_data.as_ref()) {
 							Some(x) => {
 								// println!("Adding impl trait for {}", x);
 								x.add_impl_trait(impl_trait.clone());
-								Ok(())
 							}
-							None => Err("Error adding trait to type".into()),
+							None => {
+								let name = for_type_data.as_ref().to_string();
+								res.errors.push(
+									for_type_data
+										.error(format!("Type `{}` not defined", name), ReturnValue::IdentNotDefined)
+										.suggest_similar(&name, PRIMITIVE_TYPES.iter().copied(), for_type_data.loc()),
+								)
+							}
 						}
 					} else {
-						Err("Trait doesn't match impl trait".into())
+						res.errors.push(trait_name.error(
+							format!("Implementation doesn't match trait `{}`", t),
+							ReturnValue::TypesDontMatch,
+						));
 					}
 				} else {
-					Err("Trait not defined".into())
-				};
+					let traits = trait_db.keys().map(String::as_str);
+					res.errors.push(
+						trait_name
+							.error(format!("Trait `{}` not defined", trait_name.as_ref()), ReturnValue::IdentNotDefined)
+							.suggest_similar(trait_name.as_ref(), traits, trait_name.loc()),
+					);
+				}
 				*type_db = type_db.clone().pop();
-				res
 			}
 		}
-		.expect("Error adding item");
 	}
-	Ok(())
 }
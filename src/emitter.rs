@@ -0,0 +1,109 @@
+//! Writes the errors to stderr in the format chosen with `--error-format`
+use crate::error::{self, Error, ErrorKind};
+use crate::error_codes;
+use crate::options::{ColorWhen, ErrorFormat};
+use crate::source_map::SourceMap;
+
+use serde_json::{json, Value};
+use termcolor::BufferWriter;
+
+use std::fmt::Display;
+
+pub struct Emitter {
+    format: ErrorFormat,
+    buffer_writer: BufferWriter,
+    tab_width: usize,
+    /// The SARIF log has every result, it's written by `finish`
+    sarif_results: Vec<Value>,
+    /// Return values of the errors shown that have an explanation, `finish` says how to see it
+    explained: Vec<i32>,
+}
+
+impl Emitter {
+    pub fn new(format: ErrorFormat, color: ColorWhen, tab_width: usize) -> Self {
+        Self {
+            format,
+            buffer_writer: BufferWriter::stderr(color.choice(&std::io::stderr())),
+            tab_width,
+            sarif_results: Vec::new(),
+            explained: Vec::new(),
+        }
+    }
+
+    /// Whether the errors are read by people, so they don't need to be all shown
+    pub fn is_human(&self) -> bool {
+        self.format == ErrorFormat::human
+    }
+
+    pub fn emit(&mut self, e: &Error, source_map: &SourceMap) {
+        match self.format {
+            ErrorFormat::human => {
+                e.display(source_map, &self.buffer_writer, self.tab_width);
+                if let ErrorKind::Error(errno) = e.kind() {
+                    if error_codes::explanation(errno).is_some() && !self.explained.contains(&errno) {
+                        self.explained.push(errno);
+                    }
+                }
+            }
+            ErrorFormat::json => eprintln!("{}", e.to_json(source_map)),
+            ErrorFormat::sarif => self.sarif_results.push(e.to_sarif(source_map)),
+        }
+    }
+
+    /// Emits an error that isn't in the code, like a wrong option
+    pub fn emit_non_located<T: Display>(&mut self, info: T, kind: ErrorKind) {
+        match self.format {
+            ErrorFormat::human => error::non_located_error(&self.buffer_writer, info, kind),
+            ErrorFormat::json => eprintln!(
+                "{}",
+                json!({
+                    "message": info.to_string(),
+                    "code": kind.code(),
+                    "level": kind.level(),
+                    "spans": [],
+                    "notes": [],
+                })
+            ),
+            ErrorFormat::sarif => self.sarif_results.push(json!({
+                "level": kind.sarif_level(),
+                "message": { "text": info.to_string() },
+            })),
+        }
+    }
+
+    /// Writes the SARIF log, or how to see the explanations of the errors shown to people.
+    /// The errors themselves are already written
+    pub fn finish(self) {
+        if self.format == ErrorFormat::human && !self.explained.is_empty() {
+            let mut codes = self.explained.iter().map(|&errno| error::error_code(errno)).collect::<Vec<String>>();
+            codes.sort();
+            let info = match codes.as_slice() {
+                [code] => format!("For more information about this error, try `testlang --explain {}`", code),
+                [first, ..] => format!(
+                    "Some errors have detailed explanations: {}\nFor more information about an error, try `testlang --explain {}`",
+                    codes.join(", "),
+                    first
+                ),
+                [] => unreachable!(),
+            };
+            error::non_located_error(&self.buffer_writer, info, ErrorKind::Info);
+        } else if self.format == ErrorFormat::sarif {
+            let log = json!({
+                "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+                "version": "2.1.0",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": env!("CARGO_PKG_NAME"),
+                            "version": env!("CARGO_PKG_VERSION"),
+                        },
+                    },
+                    // Columns count chars, not UTF-16 code units like SARIF's default
+                    "columnKind": "unicodeCodePoints",
+                    "results": self.sarif_results,
+                }],
+            });
+            eprintln!("{}", log);
+        }
+    }
+}
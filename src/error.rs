@@ -1,36 +1,57 @@
 use std::fmt::Display;
+use std::path::Path;
 
 use crate::color::{color, colorln};
-use crate::file_provider::{FileProvider, FileReader};
-use crate::span::location::Location;
-use crate::span::Span;
+use crate::options::ColorWhen;
+use crate::source_map::{SourceFile, SourceMap};
+use crate::span::location::{FilePosition, Location};
+use crate::span::HasLoc;
 
-use termcolor::{BufferWriter, Color, ColorChoice, ColorSpec, WriteColor};
+use serde_json::{json, Value};
+use termcolor::{Buffer, BufferWriter, Color, ColorSpec, WriteColor};
+use unicode_width::UnicodeWidthChar;
 
+/// Columns a tab is shown as, unless another width is given
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// What the program returns when it fails. Each value is also the code of its errors, `E0007` for 7,
+/// so they must never change: new values get a new number, and a removed value leaves its number unused
 #[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ReturnValue {
     #[allow(unused)]
-    Ok,
-    WrongNumberFormat,
-    UnclosedString,
-    UnclosedParens,
-    UnclosedBracket,
-    UnexpectedNonValue,
-    UnexpectedNonIdentifier,
+    Ok = 0,
+    WrongNumberFormat = 1,
+    UnclosedString = 2,
+    UnclosedParens = 3,
+    UnclosedBracket = 4,
+    UnexpectedNonValue = 5,
+    UnexpectedNonIdentifier = 6,
     // UnexpectedType,
     // ExpectedType,
     // ExpectedReturnKwd,
-    NoMain,
-    MainHasArguments,
-    MainNonVoidRetType,
-    NameDefined,
-    TypesDontMatch,
-    TraitNotImplemented,
+    NoMain = 7,
+    MainHasArguments = 8,
+    MainNonVoidRetType = 9,
+    NameDefined = 10,
+    TypesDontMatch = 11,
+    TraitNotImplemented = 12,
     // BrnchRetTypesDontMatch,
-    IdentNotDefined,
-    IntrinsicNotDefined,
-    ExpectedSemicolon,
-    UnexpectedToken,
+    IdentNotDefined = 13,
+    IntrinsicNotDefined = 14,
+    ExpectedSemicolon = 15,
+    UnexpectedToken = 16,
+    InvalidCast = 17,
+    RuntimeError = 18,
+    InvalidCharLiteral = 19,
+    InvalidEscape = 20,
+    UnclosedComment = 21,
+    UnknownCharacter = 22,
+    WrongArgumentCount = 23,
+    MissingReturn = 24,
+    DeniedLint = 25,
+    FixNotApplied = 26,
+    FileNotRead = 27,
 }
 
 impl Into<i32> for ReturnValue {
@@ -46,10 +67,42 @@ pub enum ErrorKind {
     Info,
 }
 
+/// The code of the errors with the return value `errno`, like `E0007`
+pub fn error_code(errno: i32) -> String {
+    format!("E{:04}", errno)
+}
+
+impl ErrorKind {
+    /// Name of the kind in the JSON output
+    pub fn level(&self) -> &'static str {
+        match self {
+            Self::Error(_) => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        }
+    }
+
+    /// Level of a SARIF result, which has no info
+    pub fn sarif_level(&self) -> &'static str {
+        match self {
+            Self::Error(_) => "error",
+            Self::Warning => "warning",
+            Self::Info => "note",
+        }
+    }
+
+    pub fn code(&self) -> Option<String> {
+        match self {
+            Self::Error(errno) => Some(error_code(*errno)),
+            Self::Warning | Self::Info => None,
+        }
+    }
+}
+
 impl std::fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         match self {
-            Self::Error(errno) => write!(f, "error[{}]", errno),
+            Self::Error(errno) => write!(f, "error[{}]", error_code(*errno)),
             Self::Warning => write!(f, "warning"),
             Self::Info => write!(f, "info"),
         }
@@ -59,37 +112,210 @@ impl std::fmt::Display for ErrorKind {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Error {
     loc: Location,
-    info: String,
+    /// Boxed to keep the error small, it's in the `Err` of many results
+    info: Box<str>,
     kind: ErrorKind,
-    notes: Vec<String>,
+    /// Labels, notes and helps, in the order they were added
+    annotations: Vec<Annotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Annotation {
+    Label(Label),
+    Note(Note),
+}
+
+/// Code shown with an error, with a text under it
+#[derive(Debug, Clone, PartialEq)]
+struct Label {
+    loc: Location,
+    text: String,
+    /// Primary labels are the code with the error, secondary ones are code related to it
+    primary: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Note {
+    kind: NoteKind,
+    text: String,
+    /// The change to the code suggested by a help
+    fix: Option<Fix>,
+}
+
+/// A change to the source code that fixes an error
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fix {
+    /// Replaces the code at the location with the text
+    Replace(Location, String),
+    /// Writes the text right after the location
+    InsertAfter(Location, String),
+}
+
+impl Fix {
+    pub fn loc(&self) -> &Location {
+        match self {
+            Self::Replace(loc, _) | Self::InsertAfter(loc, _) => loc,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Replace(_, text) | Self::InsertAfter(_, text) => text,
+        }
+    }
+
+    /// Where the replaced code starts and where it ends, the end isn't replaced
+    pub fn range(&self) -> (FilePosition, FilePosition) {
+        match self {
+            Self::Replace(loc, _) => (loc.start, loc.after_end()),
+            Self::InsertAfter(loc, _) => {
+                let after = loc.after_end();
+                (after, after)
+            }
+        }
+    }
+
+    /// `line`, the line where the fix is, with the fix applied. `None` if the fix changes several lines
+    fn apply_to_line(&self, line: &str) -> Option<String> {
+        let loc = self.loc();
+        if loc.start.line != loc.end.line {
+            return None;
+        }
+        let (start, end) = self.range();
+        let mut line = line.chars().collect::<Vec<char>>();
+        let start = (start.col - 1).min(line.len());
+        let end = (end.col - 1).min(line.len());
+        line.splice(start..end, self.text().chars());
+        Some(line.into_iter().collect())
+    }
+}
+
+/// What is said by a line after the code of the error
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NoteKind {
+    /// More information about the error
+    Note,
+    /// A suggestion to fix the error
+    Help,
+}
+
+impl std::fmt::Display for NoteKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            Self::Note => write!(f, "note"),
+            Self::Help => write!(f, "help"),
+        }
+    }
 }
 
 impl Error {
     pub fn new(loc: Location, info: String, kind: ErrorKind) -> Self {
         Self {
             loc,
-            info,
+            info: info.into_boxed_str(),
             kind,
-            notes: Vec::new(),
+            annotations: Vec::new(),
         }
     }
 
-    // pub fn note<T: ToString>(mut self, n: T) -> Self {
-    // 	self.notes.push(n.to_string());
-    // 	self
-    // }
+    /// Adds a text under the code of the error
+    pub fn label<T: ToString>(mut self, text: T) -> Self {
+        let loc = self.loc;
+        self.annotations.push(Annotation::Label(Label {
+            loc,
+            text: text.to_string(),
+            primary: true,
+        }));
+        self
+    }
 
-    pub fn span<T: Clone>(self, v: T) -> Span<T> {
-        Span::new(v, self.loc)
+    /// Shows the code at `loc`, that is related to the error, with a text under it
+    pub fn secondary_label<T: ToString>(mut self, loc: Location, text: T) -> Self {
+        self.annotations.push(Annotation::Label(Label {
+            loc,
+            text: text.to_string(),
+            primary: false,
+        }));
+        self
     }
 
-    pub fn default_display<FProv: FileProvider<T>, T: FileReader>(
-        &self,
-        file_provider: &FProv,
-        exit: bool,
-    ) {
-        let buffer_writer = BufferWriter::stdout(ColorChoice::Always);
-        self.display(file_provider, &buffer_writer);
+    pub fn note<T: ToString>(mut self, n: T) -> Self {
+        self.annotations.push(Annotation::Note(Note {
+            kind: NoteKind::Note,
+            text: n.to_string(),
+            fix: None,
+        }));
+        self
+    }
+
+    pub fn help<T: ToString>(mut self, h: T) -> Self {
+        self.annotations.push(Annotation::Note(Note {
+            kind: NoteKind::Help,
+            text: h.to_string(),
+            fix: None,
+        }));
+        self
+    }
+
+    /// Adds a help that suggests the change `fix`, which `--fix` can apply
+    pub fn fix<T: ToString>(mut self, h: T, fix: Fix) -> Self {
+        self.annotations.push(Annotation::Note(Note {
+            kind: NoteKind::Help,
+            text: h.to_string(),
+            fix: Some(fix),
+        }));
+        self
+    }
+
+    /// Suggests the candidates close to `name` by edit distance, they may be what was meant by it.
+    /// When only one is close, the suggestion is a fix replacing the name at `loc`
+    pub fn suggest_similar<'a, I: IntoIterator<Item = &'a str>>(self, name: &str, candidates: I, loc: &Location) -> Self {
+        // Like rustc, a third of the length can be wrong
+        let max_distance = std::cmp::max(name.chars().count(), 3) / 3;
+        let mut similar = candidates
+            .into_iter()
+            .map(|c| (strsim::levenshtein(name, c), c))
+            .filter(|&(d, _)| d > 0 && d <= max_distance)
+            .collect::<Vec<(usize, &str)>>();
+        similar.sort_unstable();
+        similar.dedup();
+        match similar.as_slice() {
+            [] => self,
+            [(_, c)] => self.fix(format!("Did you mean `{}`?", c), Fix::Replace(*loc, c.to_string())),
+            [(_, c), ..] => self.help(format!("Did you mean `{}`?", c)),
+        }
+    }
+
+    pub fn fixes(&self) -> impl Iterator<Item = &Fix> {
+        self.notes().filter_map(|n| n.fix.as_ref())
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Whether it's an error and not a warning or info
+    pub fn is_error(&self) -> bool {
+        matches!(self.kind, ErrorKind::Error(_))
+    }
+
+    /// Turns a warning into an error with the return value `r`
+    pub fn deny<R: Into<i32>>(mut self, r: R) -> Self {
+        if self.kind == ErrorKind::Warning {
+            self.kind = ErrorKind::Error(r.into());
+        }
+        self
+    }
+
+    /// Moves the error to another location
+    pub fn with_loc(mut self, loc: Location) -> Self {
+        self.loc = loc;
+        self
+    }
+
+    pub fn default_display(&self, source_map: &SourceMap, exit: bool) {
+        let buffer_writer = BufferWriter::stderr(ColorWhen::auto.choice(&std::io::stderr()));
+        self.display(source_map, &buffer_writer, DEFAULT_TAB_WIDTH);
         if exit {
             if let ErrorKind::Error(r) = self.kind {
                 std::process::exit(r);
@@ -97,14 +323,13 @@ impl Error {
         }
     }
 
-    pub fn display<FProv: FileProvider<T>, T: FileReader>(
+    /// Shows the error with the code it's about, the tabs in the code take `tab_width` columns
+    pub fn display(
         &self,
-        file_provider: &FProv,
+        source_map: &SourceMap,
         buffer_writer: &BufferWriter,
+        tab_width: usize,
     ) {
-        let notes = self.notes.clone();
-        let file = file_provider.get_file(self.loc.file());
-        //let mut r = String::new();
         let mut base_color = ColorSpec::new();
         base_color.set_bold(true);
         base_color.set_fg(Some(match self.kind {
@@ -121,106 +346,74 @@ impl Error {
         let mut default = ColorSpec::new();
         default.set_bold(false);
         default.set_fg(Some(Color::White));
+        let styles = Styles {
+            base: base_color,
+            line: line_color,
+            default,
+            tab_width,
+        };
         let mut buffer = buffer_writer.buffer();
         buffer
-            .set_color(&mut default)
+            .set_color(&styles.default)
             .expect("Error setting buffer color");
-        color(&mut buffer, self.kind.to_string(), &base_color).expect("Error setting color");
+        color(&mut buffer, self.kind.to_string(), &styles.base).expect("Error setting color");
         colorln(&mut buffer, format!(": {}", self.info), &bold).expect("Error setting color");
-        //writeln!(buffer, "{}: {}", self.kind, self.info);
-        color(&mut buffer, format!("{:>5}--> ", ""), &line_color).expect("Error setting color");
-        colorln(&mut buffer, &self.loc.to_string(), &default).expect("Error setting color");
-        //writeln!(buffer, "{:>5}--> {}", "", self.loc);
-        colorln(&mut buffer, format!("{:>5} |", ""), &line_color).expect("Error setting color");
-
-        if self.loc.start.line == self.loc.end.line {
-            let col_diff = self.loc.end.col - self.loc.start.col;
-            let mut line: String = file.line(self.loc.start.line).unwrap().into();
-            let line_num = format!("{:>5}", self.loc.start.line);
-            let empty_num = format!("{:1$}", "", line_num.len());
-            let mut space = format!("{:1$}", "", self.loc.start.col - 1);
-            for (i, c) in &mut line.clone().chars().enumerate() {
-                if i >= space.len() {
-                    break;
-                }
-                if c == '\t' {
-                    line.replace_range(i..i + 1, "    ");
-                    space.replace_range(i..i + 1, "    ");
-                }
-            }
-            color(&mut buffer, format!("{} | ", line_num), &line_color)
-                .expect("Error setting color");
-            colorln(&mut buffer, line, &default).expect("Error setting color");
-            //writeln!(buffer, "{} | {}", line_num, line);
-
-            color(&mut buffer, format!("{} | ", empty_num), &line_color)
-                .expect("Error setting color");
-            colorln(
-                &mut buffer,
-                format!("{}{:^<2$}", space, "", col_diff + 1),
-                &base_color,
-            )
-            .expect("Error setting color");
-        } else {
-            {
-                // first line
-                let mut line: String = file.line(self.loc.start.line).unwrap().into();
-                let line_num = format!("{:>5}", self.loc.start.line);
-                let mut space = format!("{:_<1$}", "", self.loc.start.col - 1);
-                for (i, c) in &mut line.clone().chars().enumerate() {
-                    if c == '\t' {
-                        line.replace_range(i..i + 1, "    ");
-                        if i < space.len() {
-                            space.replace_range(i..i + 1, "____");
-                        }
-                    }
-                }
 
-                color(&mut buffer, format!("{} |   ", line_num), &line_color)
-                    .expect("Error setting color");
-                colorln(&mut buffer, line, &default).expect("Error setting color");
-                color(&mut buffer, format!("{:>5} | ", ""), &line_color)
-                    .expect("Error setting color");
-                colorln(&mut buffer, format!(" _{}^", space), &base_color)
-                    .expect("Error setting color");
+        let labels = self.labels();
+        // Each file is shown once, in the order of its first label
+        let mut files = Vec::new();
+        for label in &labels {
+            if !files.contains(&label.loc.file()) {
+                files.push(label.loc.file());
             }
-            for line_n in self.loc.start.line + 1..=self.loc.end.line {
-                let mut line: String = file.line(line_n).unwrap().into();
-                let line_num = format!("{:>5}", line_n);
-                for (i, c) in &mut line.clone().chars().enumerate() {
-                    if c == '\t' {
-                        line.replace_range(i..i + 1, "    ");
-                    }
+        }
+        for (i, file_id) in files.into_iter().enumerate() {
+            let file = source_map.get(file_id);
+            let mut in_file = labels.iter().filter(|l| l.loc.file() == file_id).collect::<Vec<&Label>>();
+            // The error is at the first label, the other files start where their first label is
+            let arrow = if i == 0 { "-->" } else { ":::" };
+            color(&mut buffer, format!("{:>5}{} ", "", arrow), &styles.line).expect("Error setting color");
+            let position = format!("{}:{}", file.name().display(), in_file[0].loc.start);
+            colorln(&mut buffer, position, &styles.default).expect("Error setting color");
+            colorln(&mut buffer, format!("{:>5} |", ""), &styles.line).expect("Error setting color");
+            in_file.sort_by_key(|l| (l.loc.start.line, l.loc.start.col));
+            let mut last_line = None;
+            let mut rest = in_file.as_slice();
+            while let Some(label) = rest.first() {
+                if last_line.is_some_and(|last| label.loc.start.line > last + 1) {
+                    colorln(&mut buffer, "...", &styles.line).expect("Error setting color");
                 }
-                color(&mut buffer, format!("{} | ", line_num), &line_color)
-                    .expect("Error setting color");
-                color(&mut buffer, "| ", &base_color).expect("Error setting color");
-                colorln(&mut buffer, line, &default).expect("Error setting color");
-            }
-            {
-                // last line
-                let line: String = file.line(self.loc.end.line).unwrap().into();
-                let mut space = format!("{:_<1$}", "", self.loc.end.col - 1);
-                for (i, c) in &mut line.clone().chars().enumerate() {
-                    if i >= space.len() {
-                        break;
-                    }
-                    if c == '\t' {
-                        space.replace_range(i..i + 1, "____");
-                    }
+                if label.loc.start.line == label.loc.end.line {
+                    let line_n = label.loc.start.line;
+                    let same_line = rest
+                        .iter()
+                        .take_while(|l| l.loc.start.line == line_n && l.loc.end.line == line_n)
+                        .count();
+                    show_line(&mut buffer, &styles, line_n, file.line(line_n).unwrap_or(""), &rest[..same_line]);
+                    rest = &rest[same_line..];
+                    last_line = Some(line_n);
+                } else {
+                    show_lines(&mut buffer, &styles, file, label);
+                    rest = &rest[1..];
+                    last_line = Some(label.loc.end.line);
                 }
-
-                color(&mut buffer, format!("{:>5} | ", ""), &line_color)
-                    .expect("Error setting color");
-                colorln(&mut buffer, format!("|_{}^", space), &base_color)
-                    .expect("Error setting color");
             }
-            colorln(&mut buffer, format!("{:>5} |", ""), &line_color).expect("Error setting color");
         }
-        for n in &notes {
-            color(&mut buffer, format!("{:>5} = ", ""), &line_color).expect("Error setting color");
-            color(&mut buffer, "note: ", &bold).expect("Error setting color");
-            colorln(&mut buffer, n, &default).expect("Error setting color");
+        for n in self.notes() {
+            color(&mut buffer, format!("{:>5} = ", ""), &styles.line).expect("Error setting color");
+            color(&mut buffer, format!("{}: ", n.kind), &bold).expect("Error setting color");
+            colorln(&mut buffer, &n.text, &styles.default).expect("Error setting color");
+            // The line as it would be after the fix
+            let fixed = n.fix.as_ref().and_then(|fix| {
+                let line_n = fix.loc().start.line;
+                let line = source_map.get(fix.loc().file()).line(line_n)?.to_string();
+                Some((line_n, fix.apply_to_line(&line)?))
+            });
+            if let Some((line_n, line)) = fixed {
+                color(&mut buffer, format!("{:>5} | ", line_n), &styles.line)
+                    .expect("Error setting color");
+                colorln(&mut buffer, expand_tabs(&line, tab_width).0, &styles.default).expect("Error setting color");
+            }
         }
 
         buffer.reset().expect("Error resetting color");
@@ -228,11 +421,278 @@ impl Error {
             .print(&buffer)
             .expect("Error printing the buffer");
     }
+
+    /// The error as a JSON object with the locations of its labels and its notes
+    pub fn to_json(&self, source_map: &SourceMap) -> Value {
+        let spans = self
+            .labels()
+            .into_iter()
+            .map(|l| {
+                let mut span = span_json(source_map, &l.loc);
+                span["is_primary"] = l.primary.into();
+                span["label"] = if l.text.is_empty() { Value::Null } else { l.text.into() };
+                span
+            })
+            .collect::<Vec<Value>>();
+        let notes = self
+            .notes()
+            .map(|n| {
+                let suggestion = n.fix.as_ref().map(|fix| {
+                    let (start, end) = fix.range();
+                    let mut span = range_json(source_map.name(fix.loc().file()), start, end);
+                    span["replacement"] = fix.text().into();
+                    span
+                });
+                json!({
+                    "level": n.kind.to_string(),
+                    "message": n.text,
+                    "suggestion": suggestion,
+                })
+            })
+            .collect::<Vec<Value>>();
+        json!({
+            "message": self.info,
+            "code": self.kind.code(),
+            "level": self.kind.level(),
+            "spans": spans,
+            "notes": notes,
+        })
+    }
+
+    /// The error as a SARIF result, the notes are added to its message and the fixes are SARIF fixes
+    pub fn to_sarif(&self, source_map: &SourceMap) -> Value {
+        let mut message = self.info.to_string();
+        for n in self.notes() {
+            message.push_str(&format!("\n{}: {}", n.kind, n.text));
+        }
+        let (primary, secondary): (Vec<Label>, Vec<Label>) = self.labels().into_iter().partition(|l| l.primary);
+        let fixes = self
+            .fixes()
+            .map(|fix| {
+                let (start, end) = fix.range();
+                json!({
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": source_map.name(fix.loc().file()).display().to_string() },
+                        "replacements": [{
+                            "deletedRegion": sarif_region(start, end),
+                            "insertedContent": { "text": fix.text() },
+                        }],
+                    }],
+                })
+            })
+            .collect::<Vec<Value>>();
+        let mut result = json!({
+            "level": self.kind.sarif_level(),
+            "message": { "text": message },
+            "locations": primary.iter().map(|l| sarif_location(source_map, l)).collect::<Vec<Value>>(),
+            "relatedLocations": secondary.iter().map(|l| sarif_location(source_map, l)).collect::<Vec<Value>>(),
+            "fixes": fixes,
+        });
+        if let Some(code) = self.kind.code() {
+            result["ruleId"] = code.into();
+        }
+        result
+    }
+
+    fn notes(&self) -> impl Iterator<Item = &Note> {
+        self.annotations.iter().filter_map(|a| match a {
+            Annotation::Note(n) => Some(n),
+            Annotation::Label(_) => None,
+        })
+    }
+
+    /// The labels to show, the first one is the primary label at the location of the error
+    fn labels(&self) -> Vec<Label> {
+        let mut labels = vec![Label {
+            loc: self.loc,
+            text: String::new(),
+            primary: true,
+        }];
+        for a in &self.annotations {
+            if let Annotation::Label(label) = a {
+                if label.primary && label.loc == self.loc && labels[0].text.is_empty() {
+                    labels[0].text = label.text.clone();
+                } else {
+                    labels.push(label.clone());
+                }
+            }
+        }
+        labels
+    }
+}
+
+/// Where `loc` is in JSON, the columns start at 1 and the end column is the one after the span
+fn span_json(source_map: &SourceMap, loc: &Location) -> Value {
+    range_json(source_map.name(loc.file()), loc.start, loc.after_end())
 }
 
-pub fn non_located_error_default<T: Display>(info: T, kind: ErrorKind) {
-    let buffer_writer = BufferWriter::stdout(ColorChoice::Always);
-    non_located_error(&buffer_writer, info, kind);
+fn range_json(file: &Path, start: FilePosition, end: FilePosition) -> Value {
+    json!({
+        "file_name": file.display().to_string(),
+        "line_start": start.line,
+        "column_start": start.col,
+        "line_end": end.line,
+        "column_end": end.col,
+        "byte_start": start.offset,
+        "byte_end": end.offset,
+    })
+}
+
+/// A SARIF region, its end column is the one after the region like in `range_json`
+fn sarif_region(start: FilePosition, end: FilePosition) -> Value {
+    json!({
+        "startLine": start.line,
+        "startColumn": start.col,
+        "endLine": end.line,
+        "endColumn": end.col,
+        "byteOffset": start.offset,
+        "byteLength": end.offset - start.offset,
+    })
+}
+
+fn sarif_location(source_map: &SourceMap, label: &Label) -> Value {
+    let loc = &label.loc;
+    let mut location = json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": source_map.name(loc.file()).display().to_string() },
+            "region": sarif_region(loc.start, loc.after_end()),
+        },
+    });
+    if !label.text.is_empty() {
+        location["message"] = json!({ "text": label.text });
+    }
+    location
+}
+
+/// Colors used to show an error, and how wide its tabs are
+struct Styles {
+    /// The color of the kind of error, used for the primary labels
+    base: ColorSpec,
+    /// Line numbers and secondary labels
+    line: ColorSpec,
+    /// The code
+    default: ColorSpec,
+    tab_width: usize,
+}
+
+impl Styles {
+    fn label(&self, primary: bool) -> &ColorSpec {
+        if primary {
+            &self.base
+        } else {
+            &self.line
+        }
+    }
+}
+
+/// `line` with its tabs shown as `tab_width` spaces, and the column where each char is shown, with the one after the last char.
+/// Chars take as many columns as their unicode width, two for most emoji and CJK characters and none for combining ones
+fn expand_tabs(line: &str, tab_width: usize) -> (String, Vec<usize>) {
+    let mut expanded = String::new();
+    let mut cols = Vec::new();
+    let mut width = 0;
+    for c in line.chars() {
+        cols.push(width);
+        if c == '\t' {
+            expanded.push_str(&" ".repeat(tab_width));
+            width += tab_width;
+        } else {
+            expanded.push(c);
+            width += c.width().unwrap_or(0);
+        }
+    }
+    cols.push(width);
+    (expanded, cols)
+}
+
+/// Columns where the code of `loc` is shown, in a line whose columns are `cols`, the end isn't included
+fn shown_cols(cols: &[usize], start: usize, end: usize) -> (usize, usize) {
+    let col = |c: usize| cols.get(c).copied().unwrap_or(cols[cols.len() - 1] + c + 1 - cols.len());
+    let start = col(start - 1);
+    (start, std::cmp::max(col(end), start + 1))
+}
+
+/// Shows a line with the labels in it underlined, `^` for primary labels and `-` for secondary ones.
+/// The text of the last label goes after its underline, the others go below it
+fn show_line(buffer: &mut Buffer, styles: &Styles, line_n: usize, line: &str, labels: &[&Label]) {
+    let (line, cols) = expand_tabs(line, styles.tab_width);
+    color(buffer, format!("{:>5} | ", line_n), &styles.line).expect("Error setting color");
+    colorln(buffer, line, &styles.default).expect("Error setting color");
+    let mut marks: Vec<Option<bool>> = Vec::new();
+    // Primary labels are drawn last, so they are the ones seen where labels overlap
+    for primary in &[false, true] {
+        for label in labels.iter().filter(|l| l.primary == *primary) {
+            let (start, end) = shown_cols(&cols, label.loc.start.col, label.loc.end.col);
+            if marks.len() < end {
+                marks.resize(end, None);
+            }
+            for mark in &mut marks[start..end] {
+                *mark = Some(*primary);
+            }
+        }
+    }
+    color(buffer, format!("{:>5} | ", ""), &styles.line).expect("Error setting color");
+    let mut i = 0;
+    while i < marks.len() {
+        let run = marks[i..].iter().take_while(|m| **m == marks[i]).count();
+        match marks[i] {
+            Some(primary) => {
+                let mark = if primary { "^" } else { "-" };
+                color(buffer, mark.repeat(run), styles.label(primary)).expect("Error setting color")
+            }
+            None => color(buffer, " ".repeat(run), &styles.default).expect("Error setting color"),
+        }
+        i += run;
+    }
+    let mut texts = labels.iter().filter(|l| !l.text.is_empty()).collect::<Vec<&&Label>>();
+    texts.sort_by_key(|l| l.loc.start.col);
+    match texts.pop() {
+        Some(last) => colorln(buffer, format!(" {}", last.text), styles.label(last.primary)),
+        None => colorln(buffer, "", &styles.default),
+    }
+    .expect("Error setting color");
+    for label in texts.into_iter().rev() {
+        let (start, _) = shown_cols(&cols, label.loc.start.col, label.loc.end.col);
+        color(buffer, format!("{:>5} | {:2$}", "", "", start), &styles.line).expect("Error setting color");
+        colorln(buffer, &label.text, styles.label(label.primary)).expect("Error setting color");
+    }
+}
+
+/// Shows the lines of a label that spans several lines, joined by a line from its start to its end
+fn show_lines(buffer: &mut Buffer, styles: &Styles, file: &SourceFile, label: &Label) {
+    let mark = if label.primary { "^" } else { "-" };
+    let style = styles.label(label.primary);
+    let (start, end) = (label.loc.start, label.loc.end);
+    {
+        // first line
+        let (line, cols) = expand_tabs(file.line(start.line).unwrap_or(""), styles.tab_width);
+        let (col, _) = shown_cols(&cols, start.col, start.col);
+        color(buffer, format!("{:>5} |   ", start.line), &styles.line).expect("Error setting color");
+        colorln(buffer, line, &styles.default).expect("Error setting color");
+        color(buffer, format!("{:>5} | ", ""), &styles.line).expect("Error setting color");
+        colorln(buffer, format!(" _{}{}", "_".repeat(col), mark), style).expect("Error setting color");
+    }
+    for line_n in start.line + 1..=end.line {
+        let (line, _) = expand_tabs(file.line(line_n).unwrap_or(""), styles.tab_width);
+        color(buffer, format!("{:>5} | ", line_n), &styles.line).expect("Error setting color");
+        color(buffer, "| ", style).expect("Error setting color");
+        colorln(buffer, line, &styles.default).expect("Error setting color");
+    }
+    {
+        // last line
+        let (_, cols) = expand_tabs(file.line(end.line).unwrap_or(""), styles.tab_width);
+        let (_, col) = shown_cols(&cols, end.col, end.col);
+        color(buffer, format!("{:>5} | ", ""), &styles.line).expect("Error setting color");
+        let text = if label.text.is_empty() { String::new() } else { format!(" {}", label.text) };
+        colorln(buffer, format!("|_{}{}{}", "_".repeat(col - 1), mark, text), style).expect("Error setting color");
+    }
+    colorln(buffer, format!("{:>5} |", ""), &styles.line).expect("Error setting color");
+}
+
+impl HasLoc for Error {
+    fn loc(&self) -> &Location {
+        &self.loc
+    }
 }
 
 pub fn non_located_error<T: Display>(buffer_writer: &BufferWriter, info: T, kind: ErrorKind) {
@@ -0,0 +1,46 @@
+//! Long explanations of the error codes, with an example of the error and how to fix it. Shown by `--explain`
+use crate::error::ReturnValue;
+
+static EXPLANATIONS: &[(ReturnValue, &str)] = &[
+    (ReturnValue::WrongNumberFormat, include_str!("error_codes/E0001.md")),
+    (ReturnValue::UnclosedString, include_str!("error_codes/E0002.md")),
+    (ReturnValue::UnclosedParens, include_str!("error_codes/E0003.md")),
+    (ReturnValue::UnclosedBracket, include_str!("error_codes/E0004.md")),
+    (ReturnValue::UnexpectedNonValue, include_str!("error_codes/E0005.md")),
+    (ReturnValue::UnexpectedNonIdentifier, include_str!("error_codes/E0006.md")),
+    (ReturnValue::NoMain, include_str!("error_codes/E0007.md")),
+    (ReturnValue::MainHasArguments, include_str!("error_codes/E0008.md")),
+    (ReturnValue::MainNonVoidRetType, include_str!("error_codes/E0009.md")),
+    (ReturnValue::NameDefined, include_str!("error_codes/E0010.md")),
+    (ReturnValue::TypesDontMatch, include_str!("error_codes/E0011.md")),
+    (ReturnValue::TraitNotImplemented, include_str!("error_codes/E0012.md")),
+    (ReturnValue::IdentNotDefined, include_str!("error_codes/E0013.md")),
+    (ReturnValue::IntrinsicNotDefined, include_str!("error_codes/E0014.md")),
+    (ReturnValue::ExpectedSemicolon, include_str!("error_codes/E0015.md")),
+    (ReturnValue::UnexpectedToken, include_str!("error_codes/E0016.md")),
+    (ReturnValue::InvalidCast, include_str!("error_codes/E0017.md")),
+    (ReturnValue::RuntimeError, include_str!("error_codes/E0018.md")),
+    (ReturnValue::InvalidCharLiteral, include_str!("error_codes/E0019.md")),
+    (ReturnValue::InvalidEscape, include_str!("error_codes/E0020.md")),
+    (ReturnValue::UnclosedComment, include_str!("error_codes/E0021.md")),
+    (ReturnValue::UnknownCharacter, include_str!("error_codes/E0022.md")),
+    (ReturnValue::WrongArgumentCount, include_str!("error_codes/E0023.md")),
+    (ReturnValue::MissingReturn, include_str!("error_codes/E0024.md")),
+    (ReturnValue::DeniedLint, include_str!("error_codes/E0025.md")),
+    (ReturnValue::FixNotApplied, include_str!("error_codes/E0026.md")),
+    (ReturnValue::FileNotRead, include_str!("error_codes/E0027.md")),
+];
+
+/// The explanation of the error code `code`, like `E0007`. The `E` and the leading zeros can be left out
+pub fn explain(code: &str) -> Option<&'static str> {
+    let code = code.strip_prefix(|c| c == 'E' || c == 'e').unwrap_or(code);
+    explanation(code.parse().ok()?)
+}
+
+/// The explanation of the errors with the return value `errno`
+pub fn explanation(errno: i32) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(r, _)| *r as i32 == errno)
+        .map(|(_, explanation)| *explanation)
+}
@@ -1,15 +1,16 @@
 use std::path::{PathBuf, Path};
-use std::io::Read;
+use std::io::{self, Read};
 use std::fs::File as OsFile;
 
 pub trait FileReader {
 	fn contents(&self) -> &str;
-	fn line(&self, line: usize) -> Option<&str>;
-	fn name(&self) -> Option<&Path>;
 }
 
 pub trait FileProvider<File: FileReader> {
-	fn get_file<P: AsRef<Path>>(&self, p: &P) -> File;
+	/// The file at `p`, the error is `NotFound` if the provider doesn't have it
+	fn get_file<P: AsRef<Path>>(&self, p: &P) -> io::Result<File>;
+	/// Replaces the contents of the file, used to apply fixes
+	fn write_file<P: AsRef<Path>>(&self, p: &P, contents: &str) -> std::io::Result<()>;
 }
 
 
@@ -18,19 +19,17 @@ pub mod fs {
 	use super::FileProvider as FileProviderTrait;
 
 	pub struct File {
-		name: PathBuf,
 		contents: String
 	}
 
 	impl File {
-		fn new(p: PathBuf) -> Self {
+		fn new(p: PathBuf) -> io::Result<Self> {
 			let mut s = String::new();
-			let mut f = OsFile::open(&p).expect(&format!("Error opening file {}", p.display()));
-			f.read_to_string(&mut s).expect(&format!("Error reading file {}", p.display()));
-			Self {
-				name: p,
+			let mut f = OsFile::open(&p)?;
+			f.read_to_string(&mut s)?;
+			Ok(Self {
 				contents: s
-			}
+			})
 		}
 	}
 
@@ -38,15 +37,6 @@ pub mod fs {
 		fn contents(&self) -> &str {
 			&self.contents
 		}
-	
-		fn line(&self, line: usize) -> Option<&str> {
-			// println!("Querying {}:{}",self.name.display(), line);
-			self.contents.split('\n').nth(line-1).map(|line| line.trim_end())
-		}
-
-		fn name(&self) -> Option<&Path> {
-			Some(&self.name)
-		}
 	}
 
 	pub struct FileProvider{
@@ -62,60 +52,175 @@ pub mod fs {
 	}
 
 	impl FileProviderTrait<File> for FileProvider {
-		fn get_file<P: AsRef<Path>>(&self, p: &P) -> File {
+		fn get_file<P: AsRef<Path>>(&self, p: &P) -> io::Result<File> {
 			File::new(self.base_path.join(p))
 		}
+
+		/// The contents are written to a file next to it that is renamed over it, so it's never left half written
+		fn write_file<P: AsRef<Path>>(&self, p: &P, contents: &str) -> std::io::Result<()> {
+			let path = self.base_path.join(p);
+			let permissions = std::fs::metadata(&path)?.permissions();
+			// Renaming would replace it anyway
+			if permissions.readonly() {
+				return Err(io::Error::new(io::ErrorKind::PermissionDenied, "the file is read-only"));
+			}
+			let mut tmp = path.clone().into_os_string();
+			tmp.push(".fix.tmp");
+			std::fs::write(&tmp, contents)?;
+			std::fs::set_permissions(&tmp, permissions)
+				.and_then(|_| std::fs::rename(&tmp, &path))
+				.inspect_err(|_| {
+					let _ = std::fs::remove_file(&tmp);
+				})
+		}
 	}
 }
 
-pub mod repl {
+/// Files kept in memory instead of on disk, like the unsaved buffers of an editor
+pub mod memory {
 	use super::*;
 	use super::FileProvider as FileProviderTrait;
+	use std::cell::RefCell;
+	use std::collections::HashMap;
 
 	pub struct File {
 		contents: String
 	}
 
-	impl File {
-		fn new(contents: String) -> Self {
-			Self {contents}
-		}
-	}
-
 	impl FileReader for File {
 		fn contents(&self) -> &str {
 			&self.contents
 		}
-	
-		fn line(&self, line: usize) -> Option<&str> {
-			self.contents.split('\n').nth(line-1).map(|line| line.trim_end())
-		}
-
-		fn name(&self) -> Option<&Path> {
-			None
-		}
 	}
 
+	#[derive(Default)]
 	pub struct FileProvider{
-		file: String
+		/// Writing a file changes it here, so the files are behind a `RefCell`
+		files: RefCell<HashMap<PathBuf, String>>
 	}
 
 	impl FileProvider{
 		pub fn new() -> Self {
-			Self {
-				file: String::new()
-			}
+			Self::default()
 		}
 
-		pub fn new_line(&mut self, line: String) {
-			self.file = line;
+		/// Adds the file at `p`, replacing the one that was there
+		pub fn add_file<P: AsRef<Path>>(&mut self, p: &P, contents: String) {
+			self.files.get_mut().insert(p.as_ref().into(), contents);
+		}
+
+		fn contains<P: AsRef<Path>>(&self, p: &P) -> bool {
+			self.files.borrow().contains_key(p.as_ref())
 		}
 	}
 
 	impl FileProviderTrait<File> for FileProvider {
-		fn get_file<P: AsRef<Path>>(&self, _: &P) -> File {
-			File::new(self.file.clone())
+		fn get_file<P: AsRef<Path>>(&self, p: &P) -> io::Result<File> {
+			match self.files.borrow().get(p.as_ref()) {
+				Some(contents) => Ok(File { contents: contents.clone() }),
+				None => Err(io::Error::new(io::ErrorKind::NotFound, "No file in memory with that path")),
+			}
+		}
+
+		fn write_file<P: AsRef<Path>>(&self, p: &P, contents: &str) -> io::Result<()> {
+			self.files.borrow_mut().insert(p.as_ref().into(), contents.to_string());
+			Ok(())
+		}
+	}
+
+	/// The files in memory hide the ones with the same path in another provider, so an editor can check its unsaved buffers
+	/// with the rest of the files on disk. Writing a file writes it where it was read from
+	pub struct Overlay<Below> {
+		memory: FileProvider,
+		below: Below
+	}
+
+	impl<Below> Overlay<Below> {
+		pub fn new(memory: FileProvider, below: Below) -> Self {
+			Self { memory, below }
+		}
+	}
+
+	/// A file of an `Overlay`, from memory or from the provider below it
+	pub enum OverlayFile<F> {
+		Memory(File),
+		Below(F)
+	}
+
+	impl<F: FileReader> FileReader for OverlayFile<F> {
+		fn contents(&self) -> &str {
+			match self {
+				Self::Memory(f) => f.contents(),
+				Self::Below(f) => f.contents(),
+			}
+		}
+	}
+
+	impl<F: FileReader, Below: FileProviderTrait<F>> FileProviderTrait<OverlayFile<F>> for Overlay<Below> {
+		fn get_file<P: AsRef<Path>>(&self, p: &P) -> io::Result<OverlayFile<F>> {
+			match self.memory.get_file(p) {
+				Ok(f) => Ok(OverlayFile::Memory(f)),
+				Err(_) => self.below.get_file(p).map(OverlayFile::Below),
+			}
+		}
+
+		fn write_file<P: AsRef<Path>>(&self, p: &P, contents: &str) -> io::Result<()> {
+			if self.memory.contains(p) {
+				self.memory.write_file(p, contents)
+			} else {
+				self.below.write_file(p, contents)
+			}
 		}
 	}
 }
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::memory::{Overlay, OverlayFile};
+
+	fn memory(files: &[(&str, &str)]) -> memory::FileProvider {
+		let mut provider = memory::FileProvider::new();
+		for (path, contents) in files {
+			provider.add_file(path, contents.to_string());
+		}
+		provider
+	}
+
+	/// The contents of the file at `p`, and whether they came from the memory layer
+	fn read(overlay: &Overlay<memory::FileProvider>, p: &str) -> (String, bool) {
+		match overlay.get_file(&p).expect("File not found") {
+			OverlayFile::Memory(f) => (f.contents().to_string(), true),
+			OverlayFile::Below(f) => (f.contents().to_string(), false),
+		}
+	}
+
+	#[test]
+	fn memory_hides_below() {
+		let overlay = Overlay::new(
+			memory(&[("a.lang", "unsaved")]),
+			memory(&[("a.lang", "saved"), ("b.lang", "other")]),
+		);
+		assert_eq!(read(&overlay, "a.lang"), ("unsaved".to_string(), true));
+		assert_eq!(read(&overlay, "b.lang"), ("other".to_string(), false));
+	}
+
+	#[test]
+	fn writes_go_where_the_file_was_read_from() {
+		let overlay = Overlay::new(
+			memory(&[("a.lang", "unsaved")]),
+			memory(&[("a.lang", "saved"), ("b.lang", "other")]),
+		);
+		overlay.write_file(&"a.lang", "fixed a").unwrap();
+		overlay.write_file(&"b.lang", "fixed b").unwrap();
+		assert_eq!(read(&overlay, "a.lang"), ("fixed a".to_string(), true));
+		assert_eq!(read(&overlay, "b.lang"), ("fixed b".to_string(), false));
+	}
+
+	#[test]
+	fn missing_files_are_not_found() {
+		let overlay = Overlay::new(memory(&[("a.lang", "")]), memory(&[("b.lang", "")]));
+		let err = overlay.get_file(&"c.lang").err().expect("Found a file that doesn't exist");
+		assert_eq!(err.kind(), io::ErrorKind::NotFound);
+	}
+}
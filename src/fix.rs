@@ -0,0 +1,142 @@
+//! Applies the fixes suggested by the errors to the source files
+use crate::error::{Error, Fix};
+use crate::file_provider::{FileProvider, FileReader};
+use crate::source_map::{FileId, SourceMap};
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// The bytes of `contents` replaced by `fix`, `None` if they aren't in it
+fn byte_range(contents: &str, fix: &Fix) -> Option<Range<usize>> {
+	let (start, end) = fix.range();
+	let range = start.offset..end.offset;
+	contents.get(range.clone()).map(|_| range)
+}
+
+/// Applies the fixes of `errors` to the files they are in, nothing is written unless all of them apply cleanly.
+/// The fixes are applied to the contents in `source_map`, which is what the errors were found in.
+/// Returns the files that were changed, the error of a file that can't be written says which ones already were
+pub fn apply_fixes<FProv: FileProvider<T>, T: FileReader>(
+	errors: &[Error],
+	source_map: &SourceMap,
+	fprov: &FProv,
+) -> Result<Vec<PathBuf>, String> {
+	let mut by_file: HashMap<FileId, Vec<&Fix>> = HashMap::new();
+	for fix in errors.iter().flat_map(Error::fixes) {
+		by_file.entry(fix.loc().file()).or_default().push(fix);
+	}
+	let mut fixed = Vec::new();
+	for (id, fixes) in by_file {
+		let file = source_map.name(id);
+		let mut contents = source_map.get(id).contents().to_string();
+		let mut edits = fixes
+			.into_iter()
+			.map(|fix| match byte_range(&contents, fix) {
+				Some(range) => Ok((range, fix.text())),
+				None => Err(format!("The fix at {}:{} is outside of the file", file.display(), fix.loc().start)),
+			})
+			.collect::<Result<Vec<(Range<usize>, &str)>, String>>()?;
+		edits.sort_by_key(|(range, _)| (range.start, range.end));
+		// The same fix can be suggested by several errors
+		edits.dedup();
+		for pair in edits.windows(2) {
+			let (first, second) = (&pair[0].0, &pair[1].0);
+			if first.end > second.start || first.start == second.start {
+				return Err(format!("The fixes for {} overlap, no file was changed", file.display()));
+			}
+		}
+		// From the end, so the offsets of the edits left aren't moved
+		for (range, text) in edits.into_iter().rev() {
+			contents.replace_range(range, text);
+		}
+		fixed.push((file.to_path_buf(), contents));
+	}
+	for (i, (file, contents)) in fixed.iter().enumerate() {
+		if let Err(e) = fprov.write_file(file, contents) {
+			let written = fixed[..i].iter().map(|(f, _)| f.display().to_string()).collect::<Vec<String>>();
+			return Err(if written.is_empty() {
+				format!("Error writing {}: {}, no file was changed", file.display(), e)
+			} else {
+				format!("Error writing {}: {}, these files were already fixed: {}", file.display(), e, written.join(", "))
+			});
+		}
+	}
+	Ok(fixed.into_iter().map(|(file, _)| file).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::file_provider::memory;
+	use crate::span::location::{FilePosition, Location};
+	use crate::span::{Span, SpanError};
+
+	/// The files in memory, loaded into a source map
+	fn files(files: &[(&str, &str)]) -> (memory::FileProvider, SourceMap, Vec<FileId>) {
+		let mut fprov = memory::FileProvider::new();
+		for (path, contents) in files {
+			fprov.add_file(path, contents.to_string());
+		}
+		let mut source_map = SourceMap::new();
+		let ids = files.iter().map(|(path, _)| source_map.load(&fprov, path).unwrap()).collect();
+		(fprov, source_map, ids)
+	}
+
+	/// A warning whose fix replaces the bytes `start..end` of the first line of `file` with `text`
+	fn replace(source_map: &SourceMap, file: FileId, start: usize, end: usize, text: &str) -> Error {
+		let contents = source_map.get(file).contents();
+		let col = |offset: usize| contents[..offset].chars().count();
+		let loc = Location::new(file, FilePosition::new(1, col(start) + 1, start), FilePosition::new(1, col(end), end));
+		Span::new((), loc).warn("Replace").fix("Replace it", Fix::Replace(loc, text.into()))
+	}
+
+	fn contents(fprov: &memory::FileProvider, p: &str) -> String {
+		fprov.get_file(&p).unwrap().contents().to_string()
+	}
+
+	#[test]
+	fn fixes_at_the_same_point_are_rejected() {
+		let (fprov, source_map, ids) = files(&[("a.lang", "f(a)")]);
+		let errors = [replace(&source_map, ids[0], 2, 3, "b"), replace(&source_map, ids[0], 2, 3, "c")];
+		assert!(apply_fixes(&errors, &source_map, &fprov).is_err());
+		// Neither do two insertions at the same point, which one goes first is unknown
+		let insert = |text: &str| {
+			let loc = *errors[0].fixes().next().unwrap().loc();
+			Span::new((), loc).warn("Insert").fix("Insert it", Fix::InsertAfter(loc, text.into()))
+		};
+		assert!(apply_fixes(&[insert(", b"), insert(", c")], &source_map, &fprov).is_err());
+		assert_eq!(contents(&fprov, "a.lang"), "f(a)");
+	}
+
+	#[test]
+	fn same_fixes_are_applied_once() {
+		let (fprov, source_map, ids) = files(&[("a.lang", "f(a)")]);
+		let errors = [replace(&source_map, ids[0], 2, 3, "b"), replace(&source_map, ids[0], 2, 3, "b")];
+		assert_eq!(apply_fixes(&errors, &source_map, &fprov), Ok(vec![PathBuf::from("a.lang")]));
+		assert_eq!(contents(&fprov, "a.lang"), "f(b)");
+	}
+
+	#[test]
+	fn multi_byte_chars_before_the_fix() {
+		let (fprov, source_map, ids) = files(&[("a.lang", "print(\"é ü\", x)")]);
+		let start = "print(\"é ü\", ".len();
+		let errors = [replace(&source_map, ids[0], start, start + 1, "y")];
+		apply_fixes(&errors, &source_map, &fprov).unwrap();
+		assert_eq!(contents(&fprov, "a.lang"), "print(\"é ü\", y)");
+	}
+
+	#[test]
+	fn nothing_is_written_if_a_fix_fails() {
+		let (fprov, source_map, ids) = files(&[("a.lang", "f(a)"), ("b.lang", "g(a, b)")]);
+		let errors = [
+			replace(&source_map, ids[0], 2, 3, "x"),
+			replace(&source_map, ids[1], 2, 6, "x"),
+			replace(&source_map, ids[1], 5, 6, "y"),
+		];
+		let err = apply_fixes(&errors, &source_map, &fprov).unwrap_err();
+		assert!(err.ends_with("no file was changed"), "{}", err);
+		assert_eq!(contents(&fprov, "a.lang"), "f(a)");
+		assert_eq!(contents(&fprov, "b.lang"), "g(a, b)");
+	}
+}
@@ -0,0 +1,107 @@
+//! Typed tree produced by the checker and consumed by the backends.
+//! Every expression knows its type, names are resolved to a local or a function,
+//! and operators are resolved to the method of the trait implementation they use
+use crate::ast::intrinsics::Intrinsic;
+use crate::ast::{Ident, TypeData, Value};
+use crate::resolver::{SymbolId, SymbolTable};
+use crate::span::Span;
+
+use std::collections::{HashMap, HashSet};
+
+/// A checked program
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+	/// The functions of the program, and the ones of the standard library it uses
+	pub fns: HashMap<Ident, Fn>,
+	/// The methods of the trait implementations
+	pub methods: HashMap<MethodRef, Fn>,
+	/// Every binding of the program and where it's used
+	pub symbols: SymbolTable,
+}
+
+impl Program {
+	/// The functions that can be called from `root`, directly or through other functions and methods
+	pub fn reachable_fns(&self, root: &str) -> HashSet<Ident> {
+		let mut reached = HashSet::new();
+		let mut methods = HashSet::new();
+		let mut bodies = Vec::new();
+		if let Some(f) = self.fns.get(root) {
+			reached.insert(root.to_string());
+			bodies.push(&f.body);
+		}
+		while let Some(body) = bodies.pop() {
+			body.as_ref().visit(&mut |kind| match kind {
+				ExprKind::Fn(name) if reached.insert(name.clone()) => bodies.extend(self.fns.get(name).map(|f| &f.body)),
+				ExprKind::MethodCall(method, _) if methods.insert(method.clone()) => {
+					bodies.extend(self.methods.get(method).map(|f| &f.body))
+				}
+				_ => (),
+			});
+		}
+		reached
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Fn {
+	pub args: Vec<(SymbolId, TypeData)>,
+	pub body: Span<Expr>,
+}
+
+/// A method of a trait implementation: `<for_type as trait_name<defining_types>>::method`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodRef {
+	pub for_type: TypeData,
+	pub trait_name: Ident,
+	pub defining_types: Vec<TypeData>,
+	pub method: Ident,
+}
+
+#[derive(Debug, Clone)]
+pub struct Expr {
+	pub kind: ExprKind,
+	pub typ: TypeData,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
+	Value(Value),
+	/// A variable or an argument of the function
+	Local(SymbolId),
+	/// A function defined as an item
+	Fn(Ident),
+	Call(Box<Span<Expr>>, Vec<Span<Expr>>),
+	/// An operator, calls the method with the operands as arguments
+	MethodCall(MethodRef, Vec<Span<Expr>>),
+	Cast(Box<Span<Expr>>, TypeData),
+	Block(Vec<Span<Expr>>),
+	Return(Box<Span<Expr>>),
+	Define(SymbolId, Box<Span<Expr>>),
+	DefineMut(SymbolId, Box<Span<Expr>>),
+	/// An intrinsic with the arguments of the function it's in
+	CompilerIntrinsic(Intrinsic, Vec<SymbolId>),
+	/// Left where there is an error, a program with errors is never given to the backends
+	None,
+}
+
+impl Expr {
+	/// Calls `f` with the kind of this expression and of every expression in it
+	pub fn visit<'a, F: FnMut(&'a ExprKind)>(&'a self, f: &mut F) {
+		f(&self.kind);
+		match &self.kind {
+			ExprKind::Call(callee, args) => {
+				callee.as_ref().as_ref().visit(f);
+				args.iter().for_each(|a| a.as_ref().visit(f));
+			}
+			ExprKind::MethodCall(_, args) | ExprKind::Block(args) => args.iter().for_each(|a| a.as_ref().visit(f)),
+			ExprKind::Cast(e, _) | ExprKind::Return(e) | ExprKind::Define(_, e) | ExprKind::DefineMut(_, e) => {
+				e.as_ref().as_ref().visit(f)
+			}
+			ExprKind::Value(_)
+			| ExprKind::Local(_)
+			| ExprKind::Fn(_)
+			| ExprKind::CompilerIntrinsic(_, _)
+			| ExprKind::None => (),
+		}
+	}
+}
@@ -1,343 +1,160 @@
-use crate::ast::{intrinsics::Intrinsic, Block, Expr, FnSignature, HasType, Item, TypeData, Value};
-use crate::error::Error;
-use crate::file_provider::fs::FileProvider;
-use crate::scope::{self, Scope, TypeDB};
+use crate::ast::{intrinsics::Intrinsic, TypeData, Value};
+use crate::hir::{self, ExprKind};
+use crate::resolver::SymbolId;
 use crate::span::Span;
 
-use std::path::Path;
+use num_bigint::BigInt;
 
-pub fn interpret_items(items: &[Span<Item>], type_db: &mut TypeDB) {
-	run_fn(items, &String::from("main"), type_db).expect("Main function not found");
-}
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 
-pub fn load_scope() -> Scope<Value> {
-	let mut scope = Scope::root();
-	load_std(&mut scope, "src/std").unwrap();
-	scope
-}
+/// Values of the arguments and variables of the running function
+pub type Env = HashMap<SymbolId, Value>;
 
-fn run_fn(items: &[Span<Item>], fn_name: &String, type_db: &mut TypeDB) -> Result<Value, ()> {
-	let mut scope = load_scope();
-	load_items_into_scope(&mut scope, type_db, &items);
-	if let Ok(Value::Fn(_, boxed_body)) = scope.clone().get_value(fn_name) {
-		if let Expr::Block(body) = boxed_body.as_ref() {
-			return Ok(run_block(&mut scope, type_db, body));
-		}
+/// Runs `main`, the error is the message of the runtime error that stopped it
+pub fn interpret(program: &hir::Program) -> Result<(), String> {
+	let main = program.fns.get("main").expect("Main function not found");
+	match run_fn(program, main, Vec::new()) {
+		RetVal::Error(e) => Err(e),
+		_ => Ok(()),
 	}
-	Err(())
 }
 
-fn load_std<P: AsRef<Path>>(scope: &mut Scope<Value>, std_path: P) -> Result<(), Error> {
-	let fp = FileProvider::new(&std_path);
-	{
-		let tokens = match crate::tokens::tokenize("print.lang", &fp) {
-			Ok(v) => v,
-			Err(e) => return Err(e),
-		};
-		let items = match crate::parser::parse_lines(tokens, true) {
-			Ok(v) => v,
-			Err(e) => return Err(e),
-		};
-		for item in items {
-			match item.as_ref() {
-				Item::Fn(name, _, _, body) => {
-					let fn_type = item.as_ref().get_type().unwrap();
-					scope.add_variable(
-						name.val(),
-						scope::Type::NoMut(fn_type.clone()),
-						Value::Fn(fn_type, Box::new(body.val())),
-					)
-				}
-				Item::TraitDef(_, _) => Ok(()),
-				Item::ImplTrait(_, _) => Ok(()),
-			}
-			.expect("Error adding std fn");
-		}
+/// Runs `f` with only its arguments defined, gives its value unless there is a runtime error
+fn run_fn(program: &hir::Program, f: &hir::Fn, args: Vec<Value>) -> RetVal {
+	let mut env = f.args.iter().map(|(id, _)| *id).zip(args).collect();
+	match run_expr(program, &mut env, &f.body) {
+		RetVal::Return(v) => RetVal::Value(v),
+		x => x,
 	}
-	Ok(())
 }
 
-fn load_items_into_scope(scope: &mut Scope<Value>, _type_db: &TypeDB, items: &[Span<Item>]) {
-	for item in items {
-		match item.as_ref() {
-			Item::Fn(name, _, _, body) => {
-				let fn_type = item.as_ref().get_type().unwrap();
-				scope.add_variable(
-					name.val(),
-					scope::Type::NoMut(fn_type.clone()),
-					Value::Fn(fn_type, Box::new(body.val())),
-				)
-			}
-			Item::TraitDef(_, _) => Ok(()),
-			Item::ImplTrait(_, _) => Ok(()),
-		}
-		.expect("Error adding item");
-	}
-}
-
-fn run_block(scope: &mut Scope<Value>, type_db: &mut TypeDB, block: &Block) -> Value {
-	*scope = scope.clone().push();
+fn run_block(program: &hir::Program, env: &mut Env, block: &[Span<hir::Expr>]) -> RetVal {
 	for e in block {
-		match run_expr(scope, type_db, e.as_ref()) {
-			RetVal::Return(r) => return r,
-			_ => (),
+		match run_expr(program, env, e) {
+			RetVal::Value(_) => (),
+			RetVal::Return(r) => return RetVal::Value(r),
+			x => return x,
 		}
 	}
-	*scope = scope.clone().pop();
-	Value::Void
+	RetVal::Value(Value::Void)
 }
 
 pub enum RetVal {
 	Value(Value),
 	Return(Value),
+	/// A runtime error, it stops the program
+	Error(String),
 }
 
-pub fn run_expr(scope: &mut Scope<Value>, type_db: &mut TypeDB, expr: &Expr) -> RetVal {
-	RetVal::Value(match expr {
-		Expr::None => Value::Void,
-		Expr::Value(v) => v.clone(),
-		Expr::Define(id, expr) => {
-			let res = match run_expr(scope, type_db, expr.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let typ = expr.get_type(scope, type_db).unwrap();
-			scope
-				.add_variable(id.clone(), scope::Type::NoMut(typ), res)
-				.unwrap();
-			Value::Void
+/// Evaluates the arguments in order, gives the result of the first one that returns
+fn run_args(program: &hir::Program, env: &mut Env, args: &[Span<hir::Expr>]) -> Result<Vec<Value>, RetVal> {
+	let mut values = Vec::new();
+	for arg in args {
+		match run_expr(program, env, arg) {
+			RetVal::Value(v) => values.push(v),
+			x => return Err(x),
 		}
-		Expr::DefineMut(id, expr) => {
-			let res = match run_expr(scope, type_db, expr.as_ref().as_ref()) {
+	}
+	Ok(values)
+}
+
+pub fn run_expr(program: &hir::Program, env: &mut Env, expr: &Span<hir::Expr>) -> RetVal {
+	let hir::Expr { kind, typ } = expr.as_ref();
+	RetVal::Value(match kind {
+		ExprKind::None => Value::Void,
+		ExprKind::Value(v) => v.clone(),
+		ExprKind::Define(id, e) | ExprKind::DefineMut(id, e) => {
+			let res = match run_expr(program, env, e) {
 				RetVal::Value(v) => v,
 				x => return x,
 			};
-			let typ = expr.get_type(scope, type_db).unwrap();
-			scope
-				.add_variable(id.clone(), scope::Type::Mut(typ), res)
-				.unwrap();
+			env.insert(*id, res);
 			Value::Void
 		}
-		Expr::Return(e) => {
-			let res = match run_expr(scope, type_db, e.as_ref().as_ref()) {
+		ExprKind::Return(e) => {
+			let res = match run_expr(program, env, e) {
 				RetVal::Value(v) => v,
 				x => return x,
 			};
 			return RetVal::Return(res);
 		}
-		Expr::Ident(id) => {
-			// println!("IDENT: {}", id);
-			// println!("Scopeval: {:?}", scope.get_value(id));
-			scope.get_value(id).unwrap().clone()
-		}
-		Expr::Block(b) => run_block(scope, type_db, b),
-		Expr::CompilerIntrinsic(c) => return run_intrinsic(scope, c),
-		Expr::Add(rhs, lhs) => {
-			let rhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let rhs_type_data = rhs_value.get_type(scope, type_db).unwrap();
-			let rhs_type = type_db.get(&rhs_type_data);
-			let lhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let lhs_type_data = lhs_value.get_type(scope, type_db).unwrap();
-			let lhs_type = type_db.get(&lhs_type_data);
-			let impl_trait = rhs_type.get_impl_trait("Add", &[&lhs_type]).unwrap();
-			let (method_sig, method_body) = impl_trait.get_method(&"add".into()).unwrap();
-			match inner_run_fn(scope, type_db, method_sig, method_body.as_ref(), &[rhs, lhs]) {
-				RetVal::Value(v) => v,
-				x => return x
-			}
-		}
-		Expr::Sub(rhs, lhs) => {
-			let rhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let rhs_type_data = rhs_value.get_type(scope, type_db).unwrap();
-			let rhs_type = type_db.get(&rhs_type_data);
-			let lhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let lhs_type_data = lhs_value.get_type(scope, type_db).unwrap();
-			let lhs_type = type_db.get(&lhs_type_data);
-			let impl_trait = rhs_type.get_impl_trait("Sub", &[&lhs_type]).unwrap();
-			let (method_sig, method_body) = impl_trait.get_method(&"sub".into()).unwrap();
-			match inner_run_fn(scope, type_db, method_sig, method_body.as_ref(), &[rhs, lhs]) {
-				RetVal::Value(v) => v,
-				x => return x
-			}
-		}
-		Expr::Mul(rhs, lhs) => {
-			let rhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let rhs_type_data = rhs_value.get_type(scope, type_db).unwrap();
-			let rhs_type = type_db.get(&rhs_type_data);
-			let lhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let lhs_type_data = lhs_value.get_type(scope, type_db).unwrap();
-			let lhs_type = type_db.get(&lhs_type_data);
-			let impl_trait = rhs_type.get_impl_trait("Mul", &[&lhs_type]).unwrap();
-			let (method_sig, method_body) = impl_trait.get_method(&"mul".into()).unwrap();
-			match inner_run_fn(scope, type_db, method_sig, method_body.as_ref(), &[rhs, lhs]) {
-				RetVal::Value(v) => v,
-				x => return x
-			}
-		}
-		Expr::Div(rhs, lhs) => {
-			let rhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let rhs_type_data = rhs_value.get_type(scope, type_db).unwrap();
-			let rhs_type = type_db.get(&rhs_type_data);
-			let lhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let lhs_type_data = lhs_value.get_type(scope, type_db).unwrap();
-			let lhs_type = type_db.get(&lhs_type_data);
-			let impl_trait = rhs_type.get_impl_trait("Div", &[&lhs_type]).unwrap();
-			let (method_sig, method_body) = impl_trait.get_method(&"div".into()).unwrap();
-			match inner_run_fn(scope, type_db, method_sig, method_body.as_ref(), &[rhs, lhs]) {
-				RetVal::Value(v) => v,
-				x => return x
-			}
+		ExprKind::Local(id) => env[id].clone(),
+		ExprKind::Fn(id) => Value::Fn(typ.clone(), id.clone()),
+		ExprKind::Block(b) => return run_block(program, env, b),
+		ExprKind::CompilerIntrinsic(c, args) => {
+			return run_intrinsic(c, &args.iter().map(|id| &env[id]).collect::<Vec<&Value>>())
 		}
-		Expr::Exp(rhs, lhs) => {
-			let rhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
+		ExprKind::Cast(e, typ) => {
+			let value = match run_expr(program, env, e) {
 				RetVal::Value(v) => v,
 				x => return x,
 			};
-			let rhs_type_data = rhs_value.get_type(scope, type_db).unwrap();
-			let rhs_type = type_db.get(&rhs_type_data);
-			let lhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let lhs_type_data = lhs_value.get_type(scope, type_db).unwrap();
-			let lhs_type = type_db.get(&lhs_type_data);
-			let impl_trait = rhs_type.get_impl_trait("Exp", &[&lhs_type]).unwrap();
-			let (method_sig, method_body) = impl_trait.get_method(&"exp".into()).unwrap();
-			match inner_run_fn(scope, type_db, method_sig, method_body.as_ref(), &[rhs, lhs]) {
-				RetVal::Value(v) => v,
-				x => return x
-			}
+			cast_value(value, typ)
 		}
-		Expr::Eq(rhs, lhs) => {
-			let rhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
-			};
-			let rhs_type_data = rhs_value.get_type(scope, type_db).unwrap();
-			let rhs_type = type_db.get(&rhs_type_data);
-			let lhs_value = match run_expr(scope, type_db, rhs.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
+		ExprKind::MethodCall(method, args) => {
+			let args = match run_args(program, env, args) {
+				Ok(v) => v,
+				Err(x) => return x,
 			};
-			let lhs_type_data = lhs_value.get_type(scope, type_db).unwrap();
-			let lhs_type = type_db.get(&lhs_type_data);
-			let impl_trait = rhs_type.get_impl_trait("Eq", &[&lhs_type]).unwrap();
-			let (method_sig, method_body) = impl_trait.get_method(&"eq".into()).unwrap();
-			match inner_run_fn(scope, type_db, method_sig, method_body.as_ref(), &[rhs, lhs]) {
-				RetVal::Value(v) => v,
-				x => return x
-			}
+			return run_fn(program, &program.methods[method], args);
 		}
-		Expr::Neg(e) => {
-			let rhs_value = match run_expr(scope, type_db, e.as_ref().as_ref()) {
+		ExprKind::Call(callee, args) => {
+			let v = match run_expr(program, env, callee) {
 				RetVal::Value(v) => v,
 				x => return x,
 			};
-			let rhs_type_data = rhs_value.get_type(scope, type_db).unwrap();
-			let rhs_type = type_db.get(&rhs_type_data);
-			
-			let impl_trait = rhs_type.get_impl_trait("Neg", &[]).unwrap();
-			let (method_sig, method_body) = impl_trait.get_method(&"neg".into()).unwrap();
-			match inner_run_fn(scope, type_db, method_sig, method_body.as_ref(), &[e]) {
-				RetVal::Value(v) => v,
-				x => return x
-			}
-		}
-		Expr::Call(callee, args) => {
-			let v = match run_expr(scope, type_db, callee.as_ref().as_ref()) {
-				RetVal::Value(v) => v,
-				x => return x,
+			let args = match run_args(program, env, args) {
+				Ok(v) => v,
+				Err(x) => return x,
 			};
 			match v {
-				Value::Fn(typ, block) => {
-					let mut new_scope = scope.clone().push();
-					if let TypeData::Fn(FnSignature(a, _)) = typ {
-						for (arg_expr, (arg_name, arg_type)) in args.iter().zip(a.iter()) {
-							new_scope
-								.add_variable(
-									arg_name.val(),
-									scope::Type::NoMut(arg_type.val()),
-									match run_expr(scope, type_db, arg_expr.as_ref()) {
-										RetVal::Value(v) => v,
-										x => return x,
-									},
-								)
-								.unwrap();
-						}
-					} else {
-						unreachable!()
-					}
-					let ret = match run_expr(&mut new_scope, type_db, block.as_ref()) {
-						RetVal::Value(v) => v,
-						x => return x,
-					};
-					*scope = new_scope.pop();
-					ret
-				}
+				Value::Fn(_, name) => return run_fn(program, &program.fns[&name], args),
 				_ => unreachable!(),
 			}
 		}
 	})
 }
 
-fn inner_run_fn(
-	scope: &mut Scope<Value>,
-	type_db: &mut TypeDB,
-	sig: &FnSignature,
-	block: &Expr,
-	args: &[&Span<Expr>],
-) -> RetVal {
-	let mut new_scope = scope.clone().push();
-	let FnSignature(a, _) = sig;
-	for (arg_expr, (arg_name, arg_type)) in args.iter().zip(a.iter()) {
-		new_scope
-			.add_variable(
-				arg_name.val(),
-				scope::Type::NoMut(arg_type.val()),
-				match run_expr(scope, type_db, arg_expr.as_ref()) {
-					RetVal::Value(v) => v,
-					x => return x,
-				},
-			)
-			.unwrap();
+/// Converts a value with the semantics of `as`, the cast is assumed to be valid as it has already been checked.
+/// number -> integer truncates towards zero, saturating at the integer bounds (NaN becomes 0),
+/// bigint -> integer saturates at the integer bounds,
+/// integer -> char gives the replacement character (U+FFFD) for values that aren't unicode scalar values
+fn cast_value(value: Value, to: &TypeData) -> Value {
+	match (value, to) {
+		(Value::Num(n), TypeData::Integer) => Value::Int(n as i64),
+		(Value::Int(n), TypeData::Number) => Value::Num(n as f64),
+		(Value::True, TypeData::Integer) => Value::Int(1),
+		(Value::False, TypeData::Integer) => Value::Int(0),
+		(Value::Int(n), TypeData::BigInt) => Value::BigInt(BigInt::from(n)),
+		(Value::BigInt(n), TypeData::Integer) => Value::Int(i64::try_from(&n).unwrap_or_else(|_| {
+			if n.sign() == num_bigint::Sign::Minus {
+				i64::MIN
+			} else {
+				i64::MAX
+			}
+		})),
+		(Value::Char(c), TypeData::Integer) => Value::Int(c as i64),
+		(Value::Int(n), TypeData::Char) => Value::Char(
+			u32::try_from(n)
+				.ok()
+				.and_then(char::from_u32)
+				.unwrap_or(char::REPLACEMENT_CHARACTER),
+		),
+		(v, _) => v,
 	}
+}
 
-	let ret = match run_expr(&mut new_scope, type_db, block) {
-		RetVal::Value(v) => v,
-		x => return x,
-	};
-	*scope = new_scope.pop();
-	RetVal::Value(ret)
+fn runtime_error<T: std::fmt::Display>(info: T) -> RetVal {
+	RetVal::Error(info.to_string())
 }
 
-fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
+/// Runs an intrinsic with the values of the arguments of the function it's in
+fn run_intrinsic(intrinsic: &Intrinsic, args: &[&Value]) -> RetVal {
 	match intrinsic {
 		Intrinsic::Print => {
-			if let Value::Str(s) = scope.get_value(&"s".into()).unwrap() {
+			if let Value::Str(s) = args[0] {
 				println!("{}", s)
 			} else {
 				unreachable!()
@@ -345,8 +162,8 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 			RetVal::Value(Value::Void)
 		}
 		Intrinsic::AddStr => {
-			if let Value::Str(a) = scope.get_value(&"self".into()).unwrap() {
-				if let Value::Str(b) = scope.get_value(&"other".into()).unwrap() {
+			if let Value::Str(a) = args[0] {
+				if let Value::Str(b) = args[1] {
 					RetVal::Value(Value::Str(format!("{}{}", a, b)))
 				} else {
 					unreachable!()
@@ -356,8 +173,8 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 			}
 		}
 		Intrinsic::AddNum => {
-			if let Value::Num(a) = scope.get_value(&"self".into()).unwrap() {
-				if let Value::Num(b) = scope.get_value(&"other".into()).unwrap() {
+			if let Value::Num(a) = args[0] {
+				if let Value::Num(b) = args[1] {
 					RetVal::Value(Value::Num(a + b))
 				} else {
 					unreachable!()
@@ -367,8 +184,8 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 			}
 		}
 		Intrinsic::SubNum => {
-			if let Value::Num(a) = scope.get_value(&"self".into()).unwrap() {
-				if let Value::Num(b) = scope.get_value(&"other".into()).unwrap() {
+			if let Value::Num(a) = args[0] {
+				if let Value::Num(b) = args[1] {
 					RetVal::Value(Value::Num(a - b))
 				} else {
 					unreachable!()
@@ -378,8 +195,8 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 			}
 		}
 		Intrinsic::MulNum => {
-			if let Value::Num(a) = scope.get_value(&"self".into()).unwrap() {
-				if let Value::Num(b) = scope.get_value(&"other".into()).unwrap() {
+			if let Value::Num(a) = args[0] {
+				if let Value::Num(b) = args[1] {
 					RetVal::Value(Value::Num(a * b))
 				} else {
 					unreachable!()
@@ -389,8 +206,8 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 			}
 		}
 		Intrinsic::DivNum => {
-			if let Value::Num(a) = scope.get_value(&"self".into()).unwrap() {
-				if let Value::Num(b) = scope.get_value(&"other".into()).unwrap() {
+			if let Value::Num(a) = args[0] {
+				if let Value::Num(b) = args[1] {
 					RetVal::Value(Value::Num(a / b))
 				} else {
 					unreachable!()
@@ -400,8 +217,8 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 			}
 		}
 		Intrinsic::ExpNum => {
-			if let Value::Num(a) = scope.get_value(&"self".into()).unwrap() {
-				if let Value::Num(b) = scope.get_value(&"other".into()).unwrap() {
+			if let Value::Num(a) = args[0] {
+				if let Value::Num(b) = args[1] {
 					
 					RetVal::Value(Value::Num(a.powf(*b)))
 				} else {
@@ -411,10 +228,209 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 				unreachable!()
 			}
 		}
+		Intrinsic::NegNum => {
+			if let Value::Num(a) = args[0] {
+				RetVal::Value(Value::Num(-a))
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::AddInt => {
+			if let Value::Int(a) = args[0] {
+				if let Value::Int(b) = args[1] {
+					RetVal::Value(Value::Int(a.wrapping_add(*b)))
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::SubInt => {
+			if let Value::Int(a) = args[0] {
+				if let Value::Int(b) = args[1] {
+					RetVal::Value(Value::Int(a.wrapping_sub(*b)))
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::MulInt => {
+			if let Value::Int(a) = args[0] {
+				if let Value::Int(b) = args[1] {
+					RetVal::Value(Value::Int(a.wrapping_mul(*b)))
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::DivInt => {
+			if let Value::Int(a) = args[0] {
+				if let Value::Int(b) = args[1] {
+					if *b == 0 {
+						return runtime_error("Attempted to divide an integer by zero");
+					}
+					RetVal::Value(Value::Int(a.wrapping_div(*b)))
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::NegInt => {
+			if let Value::Int(a) = args[0] {
+				RetVal::Value(Value::Int(a.wrapping_neg()))
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::AddBig => {
+			if let Value::BigInt(a) = args[0] {
+				if let Value::BigInt(b) = args[1] {
+					RetVal::Value(Value::BigInt(a + b))
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::SubBig => {
+			if let Value::BigInt(a) = args[0] {
+				if let Value::BigInt(b) = args[1] {
+					RetVal::Value(Value::BigInt(a - b))
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::MulBig => {
+			if let Value::BigInt(a) = args[0] {
+				if let Value::BigInt(b) = args[1] {
+					RetVal::Value(Value::BigInt(a * b))
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::DivBig => {
+			if let Value::BigInt(a) = args[0] {
+				if let Value::BigInt(b) = args[1] {
+					if b.sign() == num_bigint::Sign::NoSign {
+						return runtime_error("Attempted to divide a bigint by zero");
+					}
+					RetVal::Value(Value::BigInt(a / b))
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::NegBig => {
+			if let Value::BigInt(a) = args[0] {
+				RetVal::Value(Value::BigInt(-a))
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::BigToStr => {
+			if let Value::BigInt(n) = args[0] {
+				RetVal::Value(Value::Str(n.to_string()))
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::ParseBig => {
+			if let Value::Str(s) = args[0] {
+				match s.trim().parse() {
+					Ok(n) => RetVal::Value(Value::BigInt(n)),
+					Err(_) => runtime_error(format!("Can't parse `{}` as a bigint", s)),
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::ToStr => RetVal::Value(Value::Str(
+			match args[0] {
+				Value::Str(s) => s.clone(),
+				Value::Char(c) => c.to_string(),
+				Value::BigInt(n) => n.to_string(),
+				v => v.to_string(),
+			},
+		)),
+		Intrinsic::StrLen => {
+			if let Value::Str(s) = args[0] {
+				RetVal::Value(Value::Int(s.chars().count() as i64))
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::StrCharAt => {
+			if let Value::Str(s) = args[0] {
+				if let Value::Int(i) = args[1] {
+					let c = usize::try_from(*i).ok().and_then(|i| s.chars().nth(i));
+					match c {
+						Some(c) => RetVal::Value(Value::Char(c)),
+						None => runtime_error(format!(
+							"Char index {} out of bounds for a string of length {}",
+							i,
+							s.chars().count()
+						)),
+					}
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::StrSlice => {
+			if let Value::Str(s) = args[0] {
+				if let (Value::Int(start), Value::Int(end)) = (
+					args[1],
+					args[2],
+				) {
+					let len = s.chars().count() as i64;
+					if *start < 0 || start > end || *end > len {
+						return runtime_error(format!(
+							"Char range {}..{} out of bounds for a string of length {}",
+							start, end, len
+						));
+					}
+					RetVal::Value(Value::Str(
+						s.chars()
+							.skip(*start as usize)
+							.take((end - start) as usize)
+							.collect(),
+					))
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::CharToStr => {
+			if let Value::Char(c) = args[0] {
+				RetVal::Value(Value::Str(c.to_string()))
+			} else {
+				unreachable!()
+			}
+		}
 
 		Intrinsic::EqNum => {
-			if let Value::Num(a) = scope.get_value(&"self".into()).unwrap() {
-				if let Value::Num(b) = scope.get_value(&"other".into()).unwrap() {
+			if let Value::Num(a) = args[0] {
+				if let Value::Num(b) = args[1] {
 					RetVal::Value(if a == b {Value::True} else {Value::False})
 				} else {
 					unreachable!()
@@ -424,8 +440,41 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 			}
 		}
 		Intrinsic::EqStr => {
-			if let Value::Str(a) = scope.get_value(&"self".into()).unwrap() {
-				if let Value::Str(b) = scope.get_value(&"other".into()).unwrap() {
+			if let Value::Str(a) = args[0] {
+				if let Value::Str(b) = args[1] {
+					RetVal::Value(if a == b {Value::True} else {Value::False})
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::EqInt => {
+			if let Value::Int(a) = args[0] {
+				if let Value::Int(b) = args[1] {
+					RetVal::Value(if a == b {Value::True} else {Value::False})
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::EqBig => {
+			if let Value::BigInt(a) = args[0] {
+				if let Value::BigInt(b) = args[1] {
+					RetVal::Value(if a == b {Value::True} else {Value::False})
+				} else {
+					unreachable!()
+				}
+			} else {
+				unreachable!()
+			}
+		}
+		Intrinsic::EqChar => {
+			if let Value::Char(a) = args[0] {
+				if let Value::Char(b) = args[1] {
 					RetVal::Value(if a == b {Value::True} else {Value::False})
 				} else {
 					unreachable!()
@@ -435,8 +484,8 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 			}
 		}
 		Intrinsic::EqBool => {
-			let a = scope.get_value(&"self".into()).unwrap();
-			let b = scope.get_value(&"other".into()).unwrap();
+			let a = args[0];
+			let b = args[1];
 			RetVal::Value(match (a, b) {
 				(Value::True, Value::True) => Value::True,
 				(Value::False, Value::False) => Value::True,
@@ -445,5 +494,23 @@ fn run_intrinsic(scope: &Scope<Value>, intrinsic: &Intrinsic) -> RetVal {
 				_ => unreachable!()
 			})
 		}
+
+		Intrinsic::LtNum | Intrinsic::LtInt | Intrinsic::LtBig => compare(args, Ordering::is_lt),
+		Intrinsic::LeNum | Intrinsic::LeInt | Intrinsic::LeBig => compare(args, Ordering::is_le),
+		Intrinsic::GtNum | Intrinsic::GtInt | Intrinsic::GtBig => compare(args, Ordering::is_gt),
+		Intrinsic::GeNum | Intrinsic::GeInt | Intrinsic::GeBig => compare(args, Ordering::is_ge),
 	}
 }
+
+/// Compares `self` and `other` (both of the same numeric type), NaN is never ordered
+fn compare(args: &[&Value], f: fn(Ordering) -> bool) -> RetVal {
+	let a = args[0];
+	let b = args[1];
+	let ordering = match (a, b) {
+		(Value::Num(a), Value::Num(b)) => a.partial_cmp(b),
+		(Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+		(Value::BigInt(a), Value::BigInt(b)) => Some(a.cmp(b)),
+		_ => unreachable!(),
+	};
+	RetVal::Value(if ordering.is_some_and(f) {Value::True} else {Value::False})
+}
@@ -0,0 +1,253 @@
+//! Lints: warnings about code that is valid but goes against a convention.
+//! Every lint has a level, changed with the `-A`, `-W` and `-D` options or with `#[allow(lint)]`-style attributes on items
+use crate::ast::Attributes;
+use crate::error::{Error, ReturnValue};
+use crate::options::Options;
+use crate::span::location::Location;
+use crate::span::{Span, SpanError};
+
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+	/// The lint isn't reported
+	Allow,
+	/// The lint is a warning
+	Warn,
+	/// The lint is an error
+	Deny,
+}
+
+impl Level {
+	/// The level of an attribute, `deny` for `#[deny(lint)]`
+	pub fn from_attribute(name: &str) -> Option<Self> {
+		Some(match name {
+			"allow" => Self::Allow,
+			"warn" => Self::Warn,
+			"deny" => Self::Deny,
+			_ => return None,
+		})
+	}
+}
+
+impl std::fmt::Display for Level {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+		match self {
+			Self::Allow => write!(f, "allow"),
+			Self::Warn => write!(f, "warn"),
+			Self::Deny => write!(f, "deny"),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Lint {
+	/// Name used by the options and the attributes
+	pub name: &'static str,
+	pub default: Level,
+}
+
+/// Parameters and variables that are never used
+pub static UNUSED_VARIABLES: Lint = Lint {
+	name: "unused_variables",
+	default: Level::Warn,
+};
+
+/// Functions and traits that are never used
+pub static DEAD_CODE: Lint = Lint {
+	name: "dead_code",
+	default: Level::Warn,
+};
+
+/// Statements that are never run, like the ones after a return
+pub static UNREACHABLE_CODE: Lint = Lint {
+	name: "unreachable_code",
+	default: Level::Warn,
+};
+
+/// Functions, parameters and variables with a name that isn't snake_case
+pub static NON_SNAKE_CASE: Lint = Lint {
+	name: "non_snake_case",
+	default: Level::Warn,
+};
+
+/// Traits with a name that isn't CamelCase
+pub static NON_CAMEL_CASE_TRAITS: Lint = Lint {
+	name: "non_camel_case_traits",
+	default: Level::Warn,
+};
+
+/// Parameters and variables that hide a function or another binding with the same name
+pub static SHADOWING: Lint = Lint {
+	name: "shadowing",
+	default: Level::Allow,
+};
+
+/// Casts to the type the value already has, they do nothing
+pub static TRIVIAL_CASTS: Lint = Lint {
+	name: "trivial_casts",
+	default: Level::Warn,
+};
+
+/// Numbers compared with `==`, they are floating point so rounding can make them differ
+pub static FLOAT_EQUALITY: Lint = Lint {
+	name: "float_equality",
+	default: Level::Allow,
+};
+
+/// Every lint, the options and attributes name them
+pub static LINTS: &[&Lint] = &[
+	&UNUSED_VARIABLES,
+	&DEAD_CODE,
+	&UNREACHABLE_CODE,
+	&NON_SNAKE_CASE,
+	&NON_CAMEL_CASE_TRAITS,
+	&SHADOWING,
+	&FLOAT_EQUALITY,
+	&TRIVIAL_CASTS,
+];
+
+/// The lint called `name`
+pub fn find(name: &str) -> Option<&'static Lint> {
+	LINTS.iter().copied().find(|l| l.name == name)
+}
+
+/// Level of every lint in every part of the program
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+	/// Levels given by the options, the other lints have their default level
+	levels: HashMap<&'static str, Level>,
+	/// Levels set by the attributes of an item, for the code inside of it
+	items: Vec<(Location, Vec<(&'static str, Level)>)>,
+	/// Every warning is an error, the allowed lints are still allowed
+	pub deny_warnings: bool,
+	/// Lints reported with their default level or denied by `deny_warnings`, the first report of each one says why
+	reported_default: HashSet<&'static str>,
+}
+
+impl LintLevels {
+	/// The levels of the `-A`, `-W` and `-D` options, applied in the order they were given so the last one wins.
+	/// Also gives the names that aren't lints
+	pub fn from_options(options: &Options) -> (Self, Vec<String>) {
+		let mut lints = Self {
+			deny_warnings: options.deny_warnings,
+			..Self::default()
+		};
+		let mut unknown = Vec::new();
+		for (name, level) in &options.lint_levels {
+			match find(name) {
+				Some(lint) => {
+					lints.levels.insert(lint.name, *level);
+				}
+				None => unknown.push(name.clone()),
+			}
+		}
+		(lints, unknown)
+	}
+
+	/// Sets the levels given by the attributes of the item at `loc`, gives a warning for each name that isn't a lint
+	pub fn add_item(&mut self, loc: &Location, attributes: &Attributes) -> Vec<Error> {
+		let mut errors = Vec::new();
+		let mut levels = Vec::new();
+		for (level, name) in &attributes.lints {
+			match find(name.as_ref()) {
+				Some(lint) => levels.push((lint.name, *level)),
+				None => errors.push(name.warn(format!("Unknown lint `{}`", name.as_ref()))),
+			}
+		}
+		if !levels.is_empty() {
+			self.items.push((*loc, levels));
+		}
+		errors
+	}
+
+	/// Level of `lint` at `loc`, the attributes of the item it's in win over the options.
+	/// `None` if the lint has its default level there
+	fn set_level(&self, lint: &Lint, loc: &Location) -> Option<Level> {
+		self.items
+			.iter()
+			.filter(|(item, _)| item.contains(loc))
+			.flat_map(|(_, levels)| levels)
+			.rev()
+			.find(|(name, _)| *name == lint.name)
+			.map(|(_, level)| *level)
+			.or_else(|| self.levels.get(lint.name).copied())
+	}
+
+	/// The error or warning for `lint` at `loc`, if it isn't allowed there
+	pub fn report<T: ToString>(&mut self, lint: &Lint, loc: &Location, info: T) -> Option<Error> {
+		let set_level = self.set_level(lint, loc);
+		let level = set_level.unwrap_or(lint.default);
+		let span = Span::new((), *loc);
+		let e = match level {
+			Level::Allow => return None,
+			Level::Warn => span.warn(info),
+			Level::Deny => span.error(info, ReturnValue::DeniedLint),
+		};
+		// Turned into an error by `apply_deny_warnings`, whatever gave it its level
+		let denied_warning = level == Level::Warn && self.deny_warnings;
+		if (denied_warning || set_level.is_none()) && self.reported_default.insert(lint.name) {
+			let note = if denied_warning {
+				"`--deny-warnings` turns this warning into an error".to_string()
+			} else {
+				format!("`#[{}({})]` on by default", lint.default, lint.name)
+			};
+			Some(e.note(note))
+		} else {
+			Some(e)
+		}
+	}
+
+	/// Turns the warnings into errors if every warning is denied
+	pub fn apply_deny_warnings(&self, errors: Vec<Error>) -> Vec<Error> {
+		if !self.deny_warnings {
+			return errors;
+		}
+		errors
+			.into_iter()
+			.map(|e| e.deny(ReturnValue::DeniedLint))
+			.collect()
+	}
+}
+
+/// Whether `name` has no uppercase letters
+pub fn is_snake_case(name: &str) -> bool {
+	!name.chars().any(char::is_uppercase)
+}
+
+/// Whether `name` starts with an uppercase letter and has no underscores, leading and trailing ones are allowed
+pub fn is_camel_case(name: &str) -> bool {
+	let name = name.trim_matches('_');
+	!name.starts_with(char::is_lowercase) && !name.contains('_')
+}
+
+/// `name` in snake_case, `fooBar` is `foo_bar`
+pub fn to_snake_case(name: &str) -> String {
+	let mut ret = String::new();
+	let mut prev_lower = false;
+	for c in name.chars() {
+		if c.is_uppercase() {
+			if prev_lower {
+				ret.push('_');
+			}
+			ret.extend(c.to_lowercase());
+			prev_lower = false;
+		} else {
+			ret.push(c);
+			prev_lower = c.is_lowercase() || c.is_numeric();
+		}
+	}
+	ret
+}
+
+/// `name` in CamelCase, `foo_bar` is `FooBar`
+pub fn to_camel_case(name: &str) -> String {
+	name.split('_')
+		.filter(|part| !part.is_empty())
+		.map(|part| {
+			let mut chars = part.chars();
+			let first = chars.next().unwrap();
+			first.to_uppercase().chain(chars).collect::<String>()
+		})
+		.collect()
+}
@@ -1,40 +1,65 @@
 mod ast;
+mod cfg;
 mod checker;
 mod color;
+mod emitter;
 mod error;
+mod error_codes;
 mod file_provider;
+mod fix;
+mod hir;
 mod interpreter;
+mod lint;
 mod operators;
 mod parser;
+mod resolver;
 mod scope;
+mod source_map;
 mod span;
 mod tokens;
-mod utils;
 
 mod backend;
 mod options;
 
 // use file_provider::{FileProvider, FileReader};
 use std::env::current_dir;
-use utils::RecursiveFn;
-
-use termcolor::{BufferWriter, ColorChoice};
+use std::io::Read;
 
 use std::time::Instant;
 
-use structopt::StructOpt;
-
 fn main() {
-    let opts = options::Options::from_args();
+    let opts = options::Options::parse();
+    if let Some(code) = &opts.explain {
+        match error_codes::explain(code) {
+            Some(explanation) => print!("{}", explanation),
+            None => structopt::clap::Error::value_validation_auto(format!("`{}` is not an error code", code)).exit(),
+        }
+        return;
+    }
     println!("{:?}", opts);
-    let fprov = file_provider::fs::FileProvider::new(&current_dir().expect("NO CWD"));
+    let input_file = opts.input_file.clone().expect("No input file");
+    let mut buffers = file_provider::memory::FileProvider::new();
+    if opts.stdin {
+        let mut contents = String::new();
+        std::io::stdin().read_to_string(&mut contents).expect("Error reading stdin");
+        buffers.add_file(&input_file, contents);
+    }
+    let fprov = file_provider::memory::Overlay::new(
+        buffers,
+        file_provider::fs::FileProvider::new(&current_dir().expect("NO CWD")),
+    );
+    let mut source_map = source_map::SourceMap::new();
+    let mut emitter = emitter::Emitter::new(opts.error_format, opts.color, opts.tab_width);
     let start_tok = Instant::now();
-    let tokens = match tokens::tokenize(&opts.input_file, &fprov) {
+    let file = match source_map.load(&fprov, &input_file) {
+        Ok(file) => file,
+        Err(e) => exit_with_read_error(&e, emitter),
+    };
+    let tokens = match tokens::tokenize(file, &source_map) {
         Ok(t) => t,
         Err(e) => {
             println!("Tokenization time: {}", start_tok.elapsed().as_secs_f32());
-            e.default_display(&fprov, true);
-            panic!("Error returned wasn't fatal")
+            exit_with_error(&e, &source_map, emitter)
         }
     };
     let tok_time = start_tok.elapsed();
@@ -45,44 +70,76 @@ fn main() {
 
     //eprintln!("{}", tokens[0].error("").display(&fprov));
 
-    let buffer_writer = BufferWriter::stdout(ColorChoice::Always);
     println!("Parsing");
     let start_parse = Instant::now();
-    match parser::parse_lines(tokens, false) {
-        Ok(items) => {
-            let parse_time = start_parse.elapsed();
-            println!("Parse time: {}", parse_time.as_secs_f32());
-            println!(
-                "Parse and tokenize time: {}",
-                (parse_time + tok_time).as_secs_f32()
-            );
-            println!("Checking");
-            let start_check = Instant::now();
-            if let Err(e) = checker::check(&items, &mut type_db, opts.lib) {
-                let check_time = start_check.elapsed();
-                println!("Check time: {}", check_time.as_secs_f32());
-                println!("Tokenize, parse and check time: {}", (parse_time + tok_time + check_time).as_secs_f32());
-                e.display(&fprov, &buffer_writer);
-                return;
+    let (items, errors) = parser::parse_lines(tokens, false);
+    let parse_time = start_parse.elapsed();
+    println!("Parse time: {}", parse_time.as_secs_f32());
+    println!(
+        "Parse and tokenize time: {}",
+        (parse_time + tok_time).as_secs_f32()
+    );
+    if !errors.is_empty() {
+        display_errors(&errors, &source_map, &mut emitter, opts.error_limit);
+        if opts.fix {
+            fix_files(&errors, &source_map, &fprov, &mut emitter);
+        }
+        emitter.finish();
+        exit_with_errors(&errors);
+    }
+    let (lints, unknown_lints) = lint::LintLevels::from_options(&opts);
+    // Like every other warning, they are errors with `--deny-warnings`
+    let unknown_lint_kind = if opts.deny_warnings {
+        error::ErrorKind::Error(error::ReturnValue::DeniedLint.into())
+    } else {
+        error::ErrorKind::Warning
+    };
+    for name in &unknown_lints {
+        emitter.emit_non_located(format!("Unknown lint `{}`", name), unknown_lint_kind);
+    }
+    let std_files = match checker::read_std(&mut source_map, "") {
+        Ok(files) => files,
+        Err(e) => exit_with_read_error(&e, emitter),
+    };
+    println!("Checking");
+    let start_check = Instant::now();
+    let program = match checker::check(file, &std_files, &items, &mut type_db, &source_map, opts.lib, lints) {
+        Ok((program, warnings)) => {
+            display_errors(&warnings, &source_map, &mut emitter, opts.error_limit);
+            if opts.fix {
+                fix_files(&warnings, &source_map, &fprov, &mut emitter);
             }
+            if let error::ErrorKind::Error(r) = unknown_lint_kind {
+                if !unknown_lints.is_empty() {
+                    emitter.finish();
+                    std::process::exit(r);
+                }
+            }
+            program
+        }
+        Err(errors) => {
             let check_time = start_check.elapsed();
             println!("Check time: {}", check_time.as_secs_f32());
             println!("Tokenize, parse and check time: {}", (parse_time + tok_time + check_time).as_secs_f32());
-            
-            println!("Codegen phase");
-            let (codegen_opts, backend) = opts.into_codegen_options();
-            backend.get_codegen().gen_code(&items, &mut type_db, codegen_opts);
-        }
-        Err(e) => {
-            let parse_time = start_parse.elapsed();
-            println!("Parse time: {}", parse_time.as_secs_f32());
-            println!(
-                "Parse and tokenize time: {}",
-                (parse_time + tok_time).as_secs_f32()
-            );
-            e.display(&fprov, &buffer_writer)
+            display_errors(&errors, &source_map, &mut emitter, opts.error_limit);
+            if opts.fix {
+                fix_files(&errors, &source_map, &fprov, &mut emitter);
+            }
+            emitter.finish();
+            exit_with_errors(&errors);
         }
     };
+    let check_time = start_check.elapsed();
+    println!("Check time: {}", check_time.as_secs_f32());
+    println!("Tokenize, parse and check time: {}", (parse_time + tok_time + check_time).as_secs_f32());
+
+    println!("Codegen phase");
+    let (codegen_opts, backend) = opts.into_codegen_options();
+    let result = backend.get_codegen().gen_code(&program, codegen_opts, &mut emitter);
+    emitter.finish();
+    if let Err(r) = result {
+        std::process::exit(r.into());
+    }
 
     // repl();
     // let ast = parser::parse_expr(tokens);
@@ -93,19 +150,93 @@ fn main() {
     //println!("{} {}", offset, tokens.len());
 }
 
+/// Emits an error that stops the compilation, and exits with its return value
+fn exit_with_error(e: &error::Error, source_map: &source_map::SourceMap, mut emitter: emitter::Emitter) -> ! {
+    emitter.emit(e, source_map);
+    emitter.finish();
+    match e.kind() {
+        error::ErrorKind::Error(r) => std::process::exit(r),
+        _ => panic!("Error returned wasn't fatal"),
+    }
+}
+
+/// Emits the error of a file that can't be read, which stops the compilation, and exits
+fn exit_with_read_error(e: &source_map::ReadError, mut emitter: emitter::Emitter) -> ! {
+    emitter.emit_non_located(e, error::ErrorKind::Error(error::ReturnValue::FileNotRead.into()));
+    emitter.finish();
+    std::process::exit(error::ReturnValue::FileNotRead.into())
+}
+
+/// Exits with the highest return value of the errors, after they have been emitted
+fn exit_with_errors(errors: &[error::Error]) -> ! {
+    let r = errors
+        .iter()
+        .filter_map(|e| match e.kind() {
+            error::ErrorKind::Error(r) => Some(r),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    std::process::exit(r)
+}
+
+/// Displays the first `limit` errors, and how many more there are. Every error is emitted if they aren't for people
+fn display_errors(
+    errors: &[error::Error],
+    source_map: &source_map::SourceMap,
+    emitter: &mut emitter::Emitter,
+    limit: usize,
+) {
+    let limit = if emitter.is_human() { limit } else { errors.len() };
+    for e in errors.iter().take(limit) {
+        emitter.emit(e, source_map);
+    }
+    if errors.len() > limit {
+        emitter.emit_non_located(
+            format!("{} more errors not shown, use `--error-limit` to show them", errors.len() - limit),
+            error::ErrorKind::Info,
+        );
+    }
+}
+
+/// Applies the fixes suggested by the errors, for `--fix`
+fn fix_files<FProv: file_provider::FileProvider<T>, T: file_provider::FileReader>(
+    errors: &[error::Error],
+    source_map: &source_map::SourceMap,
+    fprov: &FProv,
+    emitter: &mut emitter::Emitter,
+) {
+    match fix::apply_fixes(errors, source_map, fprov) {
+        Ok(files) => {
+            for file in files {
+                emitter.emit_non_located(format!("Fixed {}", file.display()), error::ErrorKind::Info);
+            }
+        }
+        Err(e) => emitter.emit_non_located(e, error::ErrorKind::Error(error::ReturnValue::FixNotApplied.into())),
+    }
+}
+
 #[allow(unused)]
 fn repl() {
     use std::io::Write;
-    let mut file_provider = file_provider::repl::FileProvider::new();
+    let mut source_map = source_map::SourceMap::new();
     let stdin = std::io::stdin();
     let mut stdout = std::io::stdout();
     let mut type_db = scope::TypeDB::new();
     let mut trait_db = checker::TraitDB::new();
-    let mut checker_scope = match checker::load_scope(&mut type_db, &mut trait_db) {
+    // Kept between lines so the functions checked for a line can be called by the next ones
+    let mut res = checker::CheckResults::default();
+    let std_files = checker::read_std(&mut source_map, "").unwrap_or_else(|e| panic!("{}", e));
+    let mut checker_scope = match checker::load_scope(&mut type_db, &mut trait_db, &mut res, &source_map, &std_files) {
         Ok(s) => s,
         Err(e) => panic!("{:?}", e),
     };
-    let mut interpreter_scope = interpreter::load_scope();
+    let mut env = interpreter::Env::new();
+    let mut emitter = emitter::Emitter::new(
+        options::ErrorFormat::human,
+        options::ColorWhen::auto,
+        error::DEFAULT_TAB_WIDTH,
+    );
     // let bool_type = type_db.get(&ast::TypeData::Bool);
     // println!("Bool is eq? {}", bool_type.get_impl_trait("Eq", &[&bool_type]).is_some());
     loop {
@@ -113,28 +244,26 @@ fn repl() {
         stdout.flush().expect("Error flushing");
         let mut line = String::new();
         stdin.read_line(&mut line).expect("Error reading line");
-        file_provider.new_line(format!("{}", line.trim_end()));
-        let tokens = tokens::tokenize("repl", &file_provider);
+        let file = source_map.add("repl".into(), line.trim_end().to_string());
+        let tokens = tokens::tokenize(file, &source_map);
         if let Err(mut e) = match tokens {
             Ok(tokens) => {
-                let ast = parser::parse_expr(&tokens, false, 0);
+                let ast = parser::parse_expr(&tokens, false);
                 match ast {
                     Ok(ast) => {
-                        if let Err(e) = checker::check_expr(&ast, &mut checker_scope, &mut type_db)
-                        {
+                        let expr = checker::check_top_level_expr(&ast, &mut checker_scope, &mut type_db, &mut res);
+                        if !res.errors.is_empty() {
+                            let e = res.errors.remove(0);
+                            res.errors.clear();
                             Err(e)
                         } else {
-                            println!(
-                                "{}",
-                                match interpreter::run_expr(
-                                    &mut interpreter_scope,
-                                    &mut type_db,
-                                    ast.as_ref()
-                                ) {
-                                    interpreter::RetVal::Value(v) => v,
-                                    interpreter::RetVal::Return(v) => v,
-                                }
-                            );
+                            match interpreter::run_expr(&res.program, &mut env, &expr) {
+                                interpreter::RetVal::Value(v) | interpreter::RetVal::Return(v) => println!("{}", v),
+                                interpreter::RetVal::Error(e) => emitter.emit_non_located(
+                                    e,
+                                    error::ErrorKind::Error(error::ReturnValue::RuntimeError.into()),
+                                ),
+                            }
                             Ok(())
                         }
                     }
@@ -143,7 +272,7 @@ fn repl() {
             }
             Err(e) => Err(e),
         } {
-            e.default_display(&file_provider, false)
+            e.default_display(&source_map, false)
         }
     }
 }
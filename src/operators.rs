@@ -1,5 +1,7 @@
 use std::convert::TryFrom;
 
+use crate::tokens::Token;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Operator {
 	Add,
@@ -7,13 +9,42 @@ pub enum Operator {
 	Mul,
 	Div,
 	Exp,
-	Eq
+	Eq,
+	Lt,
+	Le,
+	Gt,
+	Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+	Left,
+	Right,
+}
+
+/// Operators written before their operand
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrefixOperator {
+	Neg,
+}
+
+/// Operators written after their operand
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostfixOperator {
+	/// `f(a, b)`
+	Call,
+	/// `x as integer`
+	As,
 }
 
 impl Operator {
 	pub fn priority(&self) -> u8 {
 		match self {
 			Self::Eq => 20,
+			Self::Lt => 20,
+			Self::Le => 20,
+			Self::Gt => 20,
+			Self::Ge => 20,
 			Self::Add => 30,
 			Self::Sub => 30,
 			Self::Mul => 40,
@@ -21,18 +52,79 @@ impl Operator {
 			Self::Exp => 50,
 		}
 	}
+
+	pub fn associativity(&self) -> Associativity {
+		match self {
+			Self::Exp => Associativity::Right,
+			_ => Associativity::Left,
+		}
+	}
+
+	/// The priority the left operand has to be parsed with, and the one for the right operand.
+	/// A right associative operator lets an operator with its same priority continue on its right side
+	pub fn binding_power(&self) -> (u8, u8) {
+		let p = self.priority();
+		match self.associativity() {
+			Associativity::Left => (p, p + 1),
+			Associativity::Right => (p, p),
+		}
+	}
+}
+
+impl PrefixOperator {
+	/// `-2 ** 2` is `-(2 ** 2)`, but `-2 * 2` is `(-2) * 2` and `-x as integer` is `(-x) as integer`
+	pub fn priority(&self) -> u8 {
+		match self {
+			Self::Neg => 48,
+		}
+	}
+}
+
+impl PostfixOperator {
+	/// `a * b as integer` is `a * (b as integer)`, but `a ** b as integer` is `(a ** b) as integer`
+	pub fn priority(&self) -> u8 {
+		match self {
+			Self::As => 45,
+			Self::Call => 70,
+		}
+	}
+}
+
+impl TryFrom<&Token> for Operator {
+	type Error = ();
+	fn try_from(t: &Token) -> Result<Self, <Self as std::convert::TryFrom<&Token>>::Error> {
+		match t {
+			Token::Plus => Ok(Self::Add),
+			Token::Minus => Ok(Self::Sub),
+			Token::Star => Ok(Self::Mul),
+			Token::Slash => Ok(Self::Div),
+			Token::StarStar => Ok(Self::Exp),
+			Token::EqEq => Ok(Self::Eq),
+			Token::Lt => Ok(Self::Lt),
+			Token::Le => Ok(Self::Le),
+			Token::Gt => Ok(Self::Gt),
+			Token::Ge => Ok(Self::Ge),
+			_ => Err(())
+		}
+	}
+}
+
+impl TryFrom<&Token> for PrefixOperator {
+	type Error = ();
+	fn try_from(t: &Token) -> Result<Self, <Self as std::convert::TryFrom<&Token>>::Error> {
+		match t {
+			Token::Minus => Ok(Self::Neg),
+			_ => Err(())
+		}
+	}
 }
 
-impl TryFrom<&str> for Operator {
+impl TryFrom<&Token> for PostfixOperator {
 	type Error = ();
-	fn try_from(c: &str) -> Result<Self, <Self as std::convert::TryFrom<&str>>::Error> {
-		match c {
-			"+" => Ok(Self::Add),
-			"-" => Ok(Self::Sub),
-			"*" => Ok(Self::Mul),
-			"/" => Ok(Self::Div),
-			"**" => Ok(Self::Exp),
-			"==" => Ok(Self::Eq),
+	fn try_from(t: &Token) -> Result<Self, <Self as std::convert::TryFrom<&Token>>::Error> {
+		match t {
+			Token::LParen => Ok(Self::Call),
+			Token::As => Ok(Self::As),
 			_ => Err(())
 		}
 	}
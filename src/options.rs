@@ -1,6 +1,8 @@
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 use super::backend;
+use super::lint::Level;
 
 use structopt::StructOpt;
 
@@ -8,7 +10,11 @@ use structopt::StructOpt;
 #[structopt(name = "testlang")]
 pub struct Options {
     /// Path to the entry point for compilation or execution
-    pub input_file: String,
+    #[structopt(required_unless("explain"))]
+    pub input_file: Option<String>,
+    /// Shows the explanation of an error code, like `E0007`, instead of compiling
+    #[structopt(long, value_name = "CODE")]
+    pub explain: Option<String>,
     /// Is it a library (has no main function)
     #[structopt(short, long)]
     pub lib: bool,
@@ -18,9 +24,60 @@ pub struct Options {
     /// Backend to use, interpret doesn't use the out file, as it doesn't perform any kind of codegen
     #[structopt(name = "backend", short, long, default_value, possible_values(&Backend::variants()))]
     backend: Backend,
+    /// Maximum number of errors shown, the rest are only counted
+    #[structopt(long, default_value = "20")]
+    pub error_limit: usize,
+    /// Lints to allow, they aren't reported. The last of `-A`, `-W` and `-D` given for a lint wins
+    #[structopt(short = "A", long = "allow", number_of_values = 1)]
+    allow: Vec<String>,
+    /// Lints to warn about. The last of `-A`, `-W` and `-D` given for a lint wins
+    #[structopt(short = "W", long = "warn", number_of_values = 1)]
+    warn: Vec<String>,
+    /// Lints to deny, they are errors. The last of `-A`, `-W` and `-D` given for a lint wins
+    #[structopt(short = "D", long = "deny", number_of_values = 1)]
+    deny: Vec<String>,
+    /// The lints of `-A`, `-W` and `-D` with their level, in the order they were given
+    #[structopt(skip)]
+    pub lint_levels: Vec<(String, Level)>,
+    /// Turns every warning into an error
+    #[structopt(long)]
+    pub deny_warnings: bool,
+    /// Applies the suggested fixes to the source files
+    #[structopt(long)]
+    pub fix: bool,
+    /// Reads the code of the input file from stdin instead of from disk, like an unsaved buffer of an editor
+    #[structopt(long, conflicts_with = "fix")]
+    pub stdin: bool,
+    /// Format of the errors and warnings, they are written to stderr
+    #[structopt(long, default_value, possible_values(&ErrorFormat::variants()))]
+    pub error_format: ErrorFormat,
+    /// Columns a tab takes when the code of an error is shown
+    #[structopt(long, default_value = "4")]
+    pub tab_width: usize,
+    /// When to color the errors, auto only colors them if stderr is a terminal
+    #[structopt(long, default_value, possible_values(&ColorWhen::variants()))]
+    pub color: ColorWhen,
 }
 
 impl Options {
+	/// Parses the command line arguments, keeping the order of the lint levels
+	pub fn parse() -> Self {
+		let matches = Self::clap().get_matches();
+		let mut opts = Self::from_clap(&matches);
+		let mut levels = Vec::new();
+		for &(arg, names, level) in &[
+			("allow", &opts.allow, Level::Allow),
+			("warn", &opts.warn, Level::Warn),
+			("deny", &opts.deny, Level::Deny),
+		] {
+			let indices = matches.indices_of(arg).into_iter().flatten();
+			levels.extend(indices.zip(names).map(|(i, name)| (i, name.clone(), level)));
+		}
+		levels.sort_by_key(|&(i, _, _)| i);
+		opts.lint_levels = levels.into_iter().map(|(_, name, level)| (name, level)).collect();
+		opts
+	}
+
 	pub fn into_codegen_options(self) -> (CodegenOptions, Backend) {
 		(CodegenOptions {
 			lib: self.lib,
@@ -51,6 +108,50 @@ impl Default for Backend {
     }
 }
 
+arg_enum! {
+    #[derive(PartialEq, Debug, Clone, Copy)]
+    #[allow(non_camel_case_types)]
+    pub enum ErrorFormat {
+        human,
+        json,
+        sarif
+    }
+}
+
+impl Default for ErrorFormat {
+    fn default() -> Self {
+        Self::human
+    }
+}
+
+arg_enum! {
+    #[derive(PartialEq, Debug, Clone, Copy)]
+    #[allow(non_camel_case_types)]
+    pub enum ColorWhen {
+        auto,
+        always,
+        never
+    }
+}
+
+impl Default for ColorWhen {
+    fn default() -> Self {
+        Self::auto
+    }
+}
+
+impl ColorWhen {
+    /// How to color what is written to `stream`
+    pub fn choice(self, stream: &impl IsTerminal) -> termcolor::ColorChoice {
+        match self {
+            // termcolor's auto only looks at TERM and NO_COLOR, not at where the output goes
+            Self::auto if stream.is_terminal() => termcolor::ColorChoice::Auto,
+            Self::auto | Self::never => termcolor::ColorChoice::Never,
+            Self::always => termcolor::ColorChoice::Always,
+        }
+    }
+}
+
 impl Backend {
     pub fn get_codegen(&self) -> Box<dyn backend::Backend> {
         match self {
@@ -1,24 +1,26 @@
 use std::convert::TryFrom;
 
 use crate::ast;
-use crate::error::{Error, ReturnValue};
-use crate::operators::Operator;
+use crate::error::{Error, Fix, ReturnValue};
+use crate::lint::Level;
+use crate::operators::{Operator, PostfixOperator, PrefixOperator};
+use crate::span::location::Location;
 use crate::span::{HasLoc, Span, SpanError};
-use crate::tokens::Token;
+use crate::tokens::{StringPart, Token};
 
 use std::collections::HashMap;
 
 fn parse_inner(
 	i: &mut usize,
 	tokens: &[Span<Token>],
-	start: &str,
-	end: &str,
+	start: Token,
+	end: Token,
 ) -> Result<Vec<Span<Token>>, Error> {
 	let mut count = 0;
 	let mut inner_tokens = Vec::new();
 	loop {
 		if *i >= tokens.len() {
-			let mut l = tokens.last().unwrap().loc().clone();
+			let mut l = *tokens.last().unwrap().loc();
 			l.end.col += 1;
 			l.start = l.end;
 			let span = Span::new((), l);
@@ -29,9 +31,9 @@ fn parse_inner(
 			//panic!("Unexpected EOI")
 		}
 		// println!("{:?}", tokens[index + offset]);
-		if matches!(tokens[*i].clone().val(), Token::Kwd(x) if x == start) {
+		if tokens[*i].as_ref() == &start {
 			count += 1;
-		} else if matches!(tokens[*i].clone().val(), Token::Kwd(x) if x == end) {
+		} else if tokens[*i].as_ref() == &end {
 			if count == 0 {
 				*i += 1;
 				break;
@@ -44,17 +46,155 @@ fn parse_inner(
 	Ok(inner_tokens)
 }
 
+/// Parses all the items, when one has an error, it is recorded and parsing continues on the next item.
+/// Returns the items that could be parsed, and the errors found
 pub fn parse_lines(
 	tokens: Vec<Span<Token>>,
 	intrinsics: bool,
-) -> Result<Vec<Span<ast::Item>>, Error> {
+) -> (Vec<Span<ast::Item>>, Vec<Error>) {
+	// Doc comments are only attached to top level items, the nested ones are regular comments
+	let mut depth = 0;
+	let tokens: Vec<Span<Token>> = tokens
+		.into_iter()
+		.filter(|t| match t.as_ref() {
+			Token::LBrace => {
+				depth += 1;
+				true
+			}
+			Token::RBrace => {
+				depth -= 1;
+				true
+			}
+			Token::DocComment(_) => depth == 0,
+			_ => true,
+		})
+		.collect();
 	let mut i = 0;
 	let mut o = Vec::new();
+	let mut errors = Vec::new();
+	let mut attrs = ast::Attributes::default();
 	while let Some(tok) = tokens.get(i) {
 		//println!("{:?}", tok.as_ref());
 		if matches!(tok.val(), Token::EOL) {
 			i += 1;
-		} else if matches!(tok.val(), Token::Ident(x) if x == String::from("impl")) {
+		} else if let Token::DocComment(doc) = tok.as_ref() {
+			attrs.docs.push(doc.clone());
+			i += 1;
+		} else if matches!(tok.as_ref(), Token::Hash) {
+			match parse_attribute(&tokens, i) {
+				Ok((level, lints, next)) => {
+					attrs.lints.extend(lints.into_iter().map(|l| (level, l)));
+					i = next;
+				}
+				Err(e) => {
+					errors.push(e);
+					i = next_item(&tokens, i);
+				}
+			}
+		} else {
+			match parse_item(&tokens, i, intrinsics, std::mem::take(&mut attrs), &mut errors) {
+				Ok((item, next)) => {
+					o.push(item);
+					i = next;
+				}
+				Err(e) => {
+					errors.push(e);
+					i = next_item(&tokens, i);
+				}
+			}
+		}
+	}
+	if !attrs.docs.is_empty() {
+		let doc_tok = tokens.iter().rev().find(|t| matches!(t.as_ref(), Token::DocComment(_))).unwrap();
+		errors.push(doc_tok.error("Doc comment is not followed by an item", ReturnValue::UnexpectedToken));
+	}
+	if !attrs.lints.is_empty() {
+		let hash_tok = tokens.iter().rev().find(|t| matches!(t.as_ref(), Token::Hash)).unwrap();
+		errors.push(hash_tok.error("Attribute is not followed by an item", ReturnValue::UnexpectedToken));
+	}
+	(o, errors)
+}
+
+/// Finds where the item after the one starting at `start` begins, to continue parsing after an error.
+/// It's the first item keyword outside of the braces of this item, and if those aren't balanced, the first item keyword at the start of a line
+fn next_item(tokens: &[Span<Token>], start: usize) -> usize {
+	let mut depth = 0;
+	let mut line_start = None;
+	for (i, t) in tokens.iter().enumerate().skip(start + 1) {
+		match t.as_ref() {
+			Token::LBrace => depth += 1,
+			Token::RBrace => depth -= 1,
+			Token::Fn | Token::Impl | Token::Trait | Token::DocComment(_) | Token::Hash => {
+				if depth <= 0 {
+					return i;
+				}
+				if line_start.is_none() && t.loc().start.col == 1 {
+					line_start = Some(i);
+				}
+			}
+			_ => (),
+		}
+	}
+	line_start.unwrap_or(tokens.len())
+}
+
+/// Parses an attribute, `#[allow(lint, ...)]`, returns its level, the lints it names and the index of the token after it
+fn parse_attribute(
+	tokens: &[Span<Token>],
+	mut i: usize,
+) -> Result<(Level, Vec<Span<ast::Ident>>, usize), Error> {
+	// Errors after the end of the tokens are reported on the last one
+	let tok_at = |i: usize| tokens.get(i).unwrap_or_else(|| tokens.last().unwrap());
+	if tokens.get(i + 1).map(|t| t.as_ref()) != Some(&Token::LBracket) {
+		return Err(tok_at(i + 1).error("Expected `[` to start the attribute", ReturnValue::UnexpectedToken));
+	}
+	i += 2;
+	let level = match tokens.get(i).map(|t| t.as_ref()) {
+		Some(Token::Ident(name)) => match Level::from_attribute(name) {
+			Some(level) => level,
+			None => {
+				return Err(tokens[i].error(
+					format!("Unknown attribute `{}`, expected `allow`, `warn` or `deny`", name),
+					ReturnValue::UnexpectedToken,
+				))
+			}
+		},
+		_ => return Err(tok_at(i).error("Expected identfier", ReturnValue::UnexpectedNonIdentifier)),
+	};
+	i += 1;
+	if tokens.get(i).map(|t| t.as_ref()) != Some(&Token::LParen) {
+		return Err(tok_at(i).error("Expected `(`", ReturnValue::UnexpectedToken));
+	}
+	i += 1;
+	let inner = parse_inner(&mut i, tokens, Token::LParen, Token::RParen)?;
+	let mut lints = Vec::new();
+	for (n, t) in inner.iter().enumerate() {
+		match t.as_ref() {
+			Token::Ident(name) if n % 2 == 0 => lints.push(t.clone().map(name.clone())),
+			Token::Comma if n % 2 == 1 => (),
+			_ if n % 2 == 0 => {
+				return Err(t.error("Expected the name of a lint", ReturnValue::UnexpectedNonIdentifier))
+			}
+			_ => return Err(t.error("Expected `,`", ReturnValue::UnexpectedToken)),
+		}
+	}
+	if tokens.get(i).map(|t| t.as_ref()) != Some(&Token::RBracket) {
+		return Err(tok_at(i).error("Expected `]` to end the attribute", ReturnValue::UnclosedBracket));
+	}
+	Ok((level, lints, i + 1))
+}
+
+/// Parses the item starting at `i`, returns it and the index of the token after it
+fn parse_item(
+	tokens: &[Span<Token>],
+	mut i: usize,
+	intrinsics: bool,
+	mut attrs: ast::Attributes,
+	errors: &mut Vec<Error>,
+) -> Result<(Span<ast::Item>, usize), Error> {
+	let tok = &tokens[i];
+	{
+		if matches!(tok.as_ref(), Token::Impl) {
 			//println!("Matching impl");
 			let start_tok = tok;
 			i += 1;
@@ -66,7 +206,7 @@ pub fn parse_lines(
 					return Err(t.error("Expected identfier", ReturnValue::UnexpectedNonIdentifier));
 				}
 			} else {
-				let mut l = tokens.last().unwrap().loc().clone();
+				let mut l = *tokens.last().unwrap().loc();
 				l.end.col += 1;
 				l.start = l.end;
 				let span = Span::new((), l);
@@ -77,10 +217,10 @@ pub fn parse_lines(
 			};
 			let defining_types: Vec<Span<ast::Type>> = if let Some(t) = tokens.get(i) {
 				match t.val() {
-					Token::Kwd(x) if x == "<" => {
+					Token::Lt => {
 						// if true {
 						i += 1;
-						let inner_tokens = parse_inner(&mut i, &tokens, "<", ">")?;
+						let inner_tokens = parse_inner(&mut i, tokens, Token::Lt, Token::Gt)?;
 						let mut v = vec![];
 						for n in inner_tokens.split(|x| matches!(x.as_ref(), Token::Comma)) {
 							//dbg!(&n);
@@ -89,7 +229,7 @@ pub fn parse_lines(
 							if n.len() == offset {
 								v.push(nf)
 							} else {
-								let mut l = nf.loc().clone();
+								let mut l = *nf.loc();
 								l.end.col += 1;
 								l.start = l.end;
 								let span = Span::new((), l);
@@ -105,7 +245,7 @@ pub fn parse_lines(
 					_ => vec![],
 				}
 			} else {
-				let mut l = tokens.last().unwrap().loc().clone();
+				let mut l = *tokens.last().unwrap().loc();
 				l.end.col += 1;
 				l.start = l.end;
 				let span = Span::new((), l);
@@ -115,16 +255,16 @@ pub fn parse_lines(
 				));
 			};
 			let for_type = if let Some(tok) = tokens.get(i) {
-				if matches!(tok.as_ref(), Token::Ident(x) if x == "for") {
+				if matches!(tok.as_ref(), Token::For) {
 					i += 1;
-					let (for_type, offset) = parse_type(&tokens, i)?;
+					let (for_type, offset) = parse_type(tokens, i)?;
 					i += offset;
 					for_type
 				} else {
 					return Err(tok.error("Expected `for`", ReturnValue::UnexpectedNonIdentifier));
 				}
 			} else {
-				let mut l = tokens.last().unwrap().loc().clone();
+				let mut l = *tokens.last().unwrap().loc();
 				l.end.col += 1;
 				l.start = l.end;
 				let span = Span::new((), l);
@@ -135,19 +275,19 @@ pub fn parse_lines(
 			};
 			//println!("next token (should be open brace): {:?}", tokens.get(i));
 			if let Some(tok) = tokens.get(i) {
-				if matches!(tok.as_ref(), Token::Kwd(x) if x == "{") {
+				if matches!(tok.as_ref(), Token::LBrace) {
 					i += 1;
-					let inner_tokens = parse_inner(&mut i, &tokens, "{", "}")?;
+					let inner_tokens = parse_inner(&mut i, tokens, Token::LBrace, Token::RBrace)?;
 					{
-						let mut i = 0;
+						let mut idx = 0;
 						let mut type_defs = HashMap::new();
 						let mut methods = HashMap::new();
-						while let Some(tok) = inner_tokens.get(i) {
+						while let Some(tok) = inner_tokens.get(idx) {
 							match tok.as_ref() {
 								Token::EOL => (),
-								Token::Ident(x) if x == "fn" => {
-									if let ast::Item::Fn(name, args, ret, body) =
-										parse_fn(&mut i, &inner_tokens, intrinsics, true)?.unwrap()
+								Token::Fn => {
+									if let ast::Item::Fn(name, args, ret, body, _) =
+										parse_fn(&mut idx, &inner_tokens, intrinsics, true, ast::Attributes::default(), errors)?.unwrap()
 									{
 										let fn_signature = ast::FnSignature(args, Box::new(ret));
 										methods.insert(name.unwrap(), (fn_signature, body));
@@ -156,11 +296,11 @@ pub fn parse_lines(
 										unreachable!()
 									}
 								}
-								Token::Ident(x) if x == "type" => {
-									i += 1;
-									let type_name = if let Some(t) = inner_tokens.get(i) {
+								Token::Type => {
+									idx += 1;
+									let type_name = if let Some(t) = inner_tokens.get(idx) {
 										if let Token::Ident(id) = t.val() {
-											i += 1;
+											idx += 1;
 											t.clone().map(id)
 										} else {
 											return Err(t.error(
@@ -169,7 +309,7 @@ pub fn parse_lines(
 											));
 										}
 									} else {
-										let mut l = inner_tokens.last().unwrap().loc().clone();
+										let mut l = *inner_tokens.last().unwrap().loc();
 										l.end.col += 1;
 										l.start = l.end;
 										let span = Span::new((), l);
@@ -178,10 +318,10 @@ pub fn parse_lines(
 											ReturnValue::UnclosedParens,
 										));
 									};
-									// i += 1;
-									if let Some(t) = inner_tokens.get(i) {
-										if matches!(t.as_ref(), Token::Kwd(x) if x == "=") {
-											i += 1;
+									// idx += 1;
+									if let Some(t) = inner_tokens.get(idx) {
+										if matches!(t.as_ref(), Token::Assign) {
+											idx += 1;
 										} else {
 											return Err(t.error(
 												"Expected `=`",
@@ -189,7 +329,7 @@ pub fn parse_lines(
 											));
 										}
 									} else {
-										let mut l = inner_tokens.last().unwrap().loc().clone();
+										let mut l = *inner_tokens.last().unwrap().loc();
 										l.end.col += 1;
 										l.start = l.end;
 										let span = Span::new((), l);
@@ -198,19 +338,18 @@ pub fn parse_lines(
 											ReturnValue::UnclosedParens,
 										));
 									}
-									let (assigned_type, offset) = parse_type(&inner_tokens, i)?;
-									i += offset;
-									if let Some(t) = inner_tokens.get(i) {
-										if matches!(t.as_ref(), Token::Colon) {
-											i += 1;
+									let (assigned_type, offset) = parse_type(&inner_tokens, idx)?;
+									idx += offset;
+									if let Some(t) = inner_tokens.get(idx) {
+										if matches!(t.as_ref(), Token::Semicolon) {
+											idx += 1;
 										} else {
-											return Err(t.error(
-												"Expected `;`",
-												ReturnValue::ExpectedSemicolon,
-											));
+											return Err(t
+												.error("Expected `;`", ReturnValue::ExpectedSemicolon)
+												.fix("Add a `;`", Fix::InsertAfter(*inner_tokens[idx - 1].loc(), ";".into())));
 										}
 									} else {
-										let mut l = inner_tokens.last().unwrap().loc().clone();
+										let mut l = *inner_tokens.last().unwrap().loc();
 										l.end.col += 1;
 										l.start = l.end;
 										let span = Span::new((), l);
@@ -228,26 +367,26 @@ pub fn parse_lines(
 									)
 								}
 							}
-							i += 1;
+							idx += 1;
 						}
 						let last_tok = tokens[i-1].clone();
 						let impl_trait = ast::ImplTrait::new(ident, defining_types, type_defs, methods);
-						o.push(Span::join(&[start_tok.clone(), last_tok], ast::Item::ImplTrait(for_type.clone().map(for_type.as_ref().type_data().clone()), impl_trait)))
+						Ok((Span::join(&[start_tok.clone(), last_tok], ast::Item::ImplTrait(for_type.clone().map(for_type.as_ref().type_data().clone()), impl_trait, std::mem::take(&mut attrs))), i))
 					}
 				} else {
-					return Err(tok.error("Expected `{`", ReturnValue::UnclosedBracket));
+					Err(tok.error("Expected `{`", ReturnValue::UnclosedBracket))
 				}
 			} else {
-				let mut l = tokens.last().unwrap().loc().clone();
+				let mut l = *tokens.last().unwrap().loc();
 				l.end.col += 1;
 				l.start = l.end;
 				let span = Span::new((), l);
-				return Err(span.error("Unexpected EOI, expected `{`", ReturnValue::UnclosedParens));
-			};
+				Err(span.error("Unexpected EOI, expected `{`", ReturnValue::UnclosedParens))
+			}
 
 		//println!("Name: {}", ident);
 		//println!("Defining types: {:?}", defining_types);
-		} else if matches!(tok.val(), Token::Ident(x) if x == String::from("trait")) {
+		} else if matches!(tok.as_ref(), Token::Trait) {
 			//println!("Matching trait");
 			let start_tok = tok;
 			i += 1;
@@ -259,7 +398,7 @@ pub fn parse_lines(
 					return Err(t.error("Expected identfier", ReturnValue::UnexpectedNonIdentifier));
 				}
 			} else {
-				let mut l = tokens.last().unwrap().loc().clone();
+				let mut l = *tokens.last().unwrap().loc();
 				l.end.col += 1;
 				l.start = l.end;
 				let span = Span::new((), l);
@@ -270,10 +409,10 @@ pub fn parse_lines(
 			};
 			let defining_types: Vec<Span<ast::Ident>> = if let Some(t) = tokens.get(i) {
 				match t.val() {
-					Token::Kwd(x) if x == "<" => {
+					Token::Lt => {
 						// if true {
 						i += 1;
-						let inner_tokens = parse_inner(&mut i, &tokens, "<", ">")?;
+						let inner_tokens = parse_inner(&mut i, tokens, Token::Lt, Token::Gt)?;
 						let mut v = vec![];
 						for (i, n) in inner_tokens
 							.split(|x| matches!(x.as_ref(), Token::Comma))
@@ -290,7 +429,7 @@ pub fn parse_lines(
 										));
 									}
 								} else {
-									let mut l = nf.loc().clone();
+									let mut l = *nf.loc();
 									l.end.col += 1;
 									l.start = l.end;
 									let span = Span::new((), l);
@@ -312,7 +451,7 @@ pub fn parse_lines(
 					_ => vec![],
 				}
 			} else {
-				let mut l = tokens.last().unwrap().loc().clone();
+				let mut l = *tokens.last().unwrap().loc();
 				l.end.col += 1;
 				l.start = l.end;
 				let span = Span::new((), l);
@@ -323,10 +462,10 @@ pub fn parse_lines(
 			};
 			//println!("Trait {}{:?}", ident, defining_types);
 			if let Some(t) = tokens.get(i) {
-				if matches!(t.as_ref(), Token::Kwd(x) if x == "{") {
+				if matches!(t.as_ref(), Token::LBrace) {
 					// if true {
 					i += 1;
-					let inner_tokens = parse_inner(&mut i, &tokens, "{", "}")?;
+					let inner_tokens = parse_inner(&mut i, tokens, Token::LBrace, Token::RBrace)?;
 					let mut idx = 0;
 					let mut type_defs = Vec::new();
 					let mut methods = HashMap::new();
@@ -335,7 +474,7 @@ pub fn parse_lines(
 						if let Token::EOL = inner_tokens[idx].as_ref() {
 							idx += 1;
 							continue;
-						} else if matches!(inner_tokens[idx].as_ref(), Token::Ident(x) if x == "type")
+						} else if matches!(inner_tokens[idx].as_ref(), Token::Type)
 						{
 							idx += 1;
 							type_defs.push(if let Some(tok) = inner_tokens.get(idx) {
@@ -343,17 +482,16 @@ pub fn parse_lines(
 									let id = tok.clone().map(id);
 									idx += 1;
 									if let Some(tok) = inner_tokens.get(idx) {
-										if let Token::Colon = tok.as_ref() {
+										if let Token::Semicolon = tok.as_ref() {
 											//println!("TYPEDEF: {}", id);
 											id
 										} else {
-											return Err(tok.error(
-												"Expected `;`",
-												ReturnValue::ExpectedSemicolon,
-											));
+											return Err(tok
+												.error("Expected `;`", ReturnValue::ExpectedSemicolon)
+												.fix("Add a `;`", Fix::InsertAfter(*id.loc(), ";".into())));
 										}
 									} else {
-										let mut l = inner_tokens.last().unwrap().loc().clone();
+										let mut l = *inner_tokens.last().unwrap().loc();
 										l.end.col += 1;
 										l.start = l.end;
 										let span = Span::new((), l);
@@ -369,7 +507,7 @@ pub fn parse_lines(
 									));
 								}
 							} else {
-								let mut l = inner_tokens.last().unwrap().loc().clone();
+								let mut l = *inner_tokens.last().unwrap().loc();
 								l.end.col += 1;
 								l.start = l.end;
 								let span = Span::new((), l);
@@ -378,7 +516,7 @@ pub fn parse_lines(
 									ReturnValue::UnclosedParens,
 								));
 							});
-						} else if matches!(inner_tokens[idx].as_ref(), Token::Ident(x) if x == "fn")
+						} else if matches!(inner_tokens[idx].as_ref(), Token::Fn)
 						{
 							idx += 1;
 							//println!("METHOD {} => {:?}", idx, inner_tokens[idx]);
@@ -393,7 +531,7 @@ pub fn parse_lines(
 									));
 								}
 							} else {
-								let mut l = inner_tokens.last().unwrap().loc().clone();
+								let mut l = *inner_tokens.last().unwrap().loc();
 								l.end.col += 1;
 								l.start = l.end;
 								let span = Span::new((), l);
@@ -409,11 +547,11 @@ pub fn parse_lines(
 								);
 							}
 							let args = if let Some(t) = inner_tokens.get(idx) {
-								if matches!(t.val(), Token::Kwd(x) if x == "(") {
+								if matches!(t.as_ref(), Token::LParen) {
 									// if true {
 									idx += 1;
 									let inner_inner_tokens =
-										parse_inner(&mut idx, &inner_tokens, "(", ")")?;
+										parse_inner(&mut idx, &inner_tokens, Token::LParen, Token::RParen)?;
 									let mut res = Vec::new();
 									for arg in inner_inner_tokens
 										.split(|tok| matches!(tok.val(), Token::Comma))
@@ -436,7 +574,7 @@ pub fn parse_lines(
 											name.clone().map(ast::TypeData::SelfRef)
 										} else {
 											if let Some(t) = arg.get(1) {
-												if let Token::Semicolon = t.val() {
+												if let Token::Colon = t.val() {
 												} else {
 													return Err(t.error(
 														"Expected semicolon",
@@ -444,7 +582,7 @@ pub fn parse_lines(
 													));
 												}
 											} else {
-												let mut l = arg.last().unwrap().loc().clone();
+												let mut l = *arg.last().unwrap().loc();
 												l.end.col += 1;
 												l.start = l.end;
 												let span = Span::new((), l);
@@ -470,7 +608,7 @@ pub fn parse_lines(
 									));
 								}
 							} else {
-								let mut l = inner_tokens.last().unwrap().loc().clone();
+								let mut l = *inner_tokens.last().unwrap().loc();
 								l.end.col += 1;
 								l.start = l.end;
 								let span = Span::new((), l);
@@ -481,11 +619,11 @@ pub fn parse_lines(
 							};
 							//println!("ARGS: {:?}", args);
 							let ret_type = if let Some(t) = inner_tokens.get(idx) {
-								if matches!(t.val(), Token::Kwd(k) if k == "->") {
+								if matches!(t.as_ref(), Token::Arrow) {
 									idx += 1;
 									let ty = match parse_type(&inner_tokens, idx) {
 										Ok((v, off)) => {
-											i += off;
+											idx += off;
 											v
 										}
 										Err(e) => return Err(e),
@@ -495,7 +633,7 @@ pub fn parse_lines(
 									t.clone().map(ast::TypeData::Void)
 								}
 							} else {
-								let mut l = inner_tokens.last().unwrap().loc().clone();
+								let mut l = *inner_tokens.last().unwrap().loc();
 								l.end.col += 1;
 								l.start = l.end;
 								let span = Span::new((), l);
@@ -504,18 +642,17 @@ pub fn parse_lines(
 									ReturnValue::UnclosedParens,
 								));
 							};
-							idx += 1;
 							if let Some(tok) = inner_tokens.get(idx) {
-								if let Token::Colon = tok.as_ref() {
+								if let Token::Semicolon = tok.as_ref() {
 									let fn_sig = ast::FnSignature(args, Box::new(ret_type));
 									methods.insert(ident.unwrap(), fn_sig);
 								} else {
-									return Err(
-										tok.error("Expected `;`", ReturnValue::ExpectedSemicolon)
-									);
+									return Err(tok
+										.error("Expected `;`", ReturnValue::ExpectedSemicolon)
+										.fix("Add a `;`", Fix::InsertAfter(*inner_tokens[idx - 1].loc(), ";".into())));
 								}
 							} else {
-								let mut l = inner_tokens.last().unwrap().loc().clone();
+								let mut l = *inner_tokens.last().unwrap().loc();
 								l.end.col += 1;
 								l.start = l.end;
 								let span = Span::new((), l);
@@ -531,28 +668,31 @@ pub fn parse_lines(
 					//println!("Methods: {:?}", methods);
 					//println!("TYPEDEFS: {:?}", type_defs);
 					let last_tok = tokens[i - 1].clone();
-					o.push(Span::join(
+					Ok((Span::join(
 						&[start_tok.clone(), last_tok],
 						ast::Item::TraitDef(
 							ident.clone(),
 							ast::Trait::new(ident, defining_types, type_defs, methods),
+							std::mem::take(&mut attrs),
 						),
-					))
-				} // TODO Error out on not {
+					), i))
+				} else {
+					Err(t.error("Expected `{`", ReturnValue::UnclosedBracket))
+				}
 			} else {
-				let mut l = tokens.last().unwrap().loc().clone();
+				let mut l = *tokens.last().unwrap().loc();
 				l.end.col += 1;
 				l.start = l.end;
 				let span = Span::new((), l);
-				return Err(span.error("Unexpected EOI, expected `{`", ReturnValue::UnclosedParens));
+				Err(span.error("Unexpected EOI, expected `{`", ReturnValue::UnclosedParens))
 			}
-		} else if matches!(tok.val(), Token::Ident(x) if x == String::from("fn")) {
-			o.push(parse_fn(&mut i, &tokens, intrinsics, false)?)
+		} else if matches!(tok.as_ref(), Token::Fn) {
+			let f = parse_fn(&mut i, tokens, intrinsics, false, attrs, errors)?;
+			Ok((f, i))
 		} else {
-			return Err(tok.error("Unexpected token", ReturnValue::UnexpectedToken));
+			Err(tok.error("Unexpected token", ReturnValue::UnexpectedToken))
 		}
 	}
-	Ok(o)
 }
 
 fn parse_fn(
@@ -560,6 +700,8 @@ fn parse_fn(
 	tokens: &[Span<Token>],
 	intrinsics: bool,
 	is_method: bool,
+	attrs: ast::Attributes,
+	errors: &mut Vec<Error>,
 ) -> Result<Span<ast::Item>, Error> {
 	*i += 1;
 	let ident = if let Some(t) = tokens.get(*i) {
@@ -570,7 +712,7 @@ fn parse_fn(
 			return Err(t.error("Expected identfier", ReturnValue::UnexpectedNonIdentifier));
 		}
 	} else {
-		let mut l = tokens.last().unwrap().loc().clone();
+		let mut l = *tokens.last().unwrap().loc();
 		l.end.col += 1;
 		l.start = l.end;
 		let span = Span::new((), l);
@@ -581,10 +723,10 @@ fn parse_fn(
 	};
 	// println!("FN NAME: {}", ident);
 	let args = if let Some(t) = tokens.get(*i) {
-		if matches!(t.val(), Token::Kwd(x) if x == "(") {
+		if matches!(t.as_ref(), Token::LParen) {
 			// if true {
 			*i += 1;
-			let inner_tokens = parse_inner(i, &tokens, "(", ")")?;
+			let inner_tokens = parse_inner(i, &tokens, Token::LParen, Token::RParen)?;
 			//println!("{:?}", tokens.get(i));
 			let mut res = Vec::new();
 			let mut first = true;
@@ -608,7 +750,7 @@ fn parse_fn(
 					name.clone().map(ast::TypeData::SelfRef)
 				} else {
 					if let Some(t) = arg.get(1) {
-						if let Token::Semicolon = t.val() {
+						if let Token::Colon = t.val() {
 						} else {
 							return Err(t.error(
 								"Expected semicolon",
@@ -616,7 +758,7 @@ fn parse_fn(
 							));
 						}
 					} else {
-						let mut l = arg.last().unwrap().loc().clone();
+						let mut l = *arg.last().unwrap().loc();
 						l.end.col += 1;
 						l.start = l.end;
 						let span = Span::new((), l);
@@ -638,7 +780,7 @@ fn parse_fn(
 			return Err(t.error("Expected `(`", ReturnValue::UnexpectedNonIdentifier));
 		}
 	} else {
-		let mut l = tokens.last().unwrap().loc().clone();
+		let mut l = *tokens.last().unwrap().loc();
 		l.end.col += 1;
 		l.start = l.end;
 		let span = Span::new((), l);
@@ -647,7 +789,7 @@ fn parse_fn(
 	// println!("ARGS: {:?}", args);
 
 	let ret_type = if let Some(t) = tokens.get(*i) {
-		if matches!(t.val(), Token::Kwd(k) if k == "->") {
+		if matches!(t.as_ref(), Token::Arrow) {
 			*i += 1;
 			let ty = match parse_type(&tokens, *i) {
 				Ok((v, off)) => {
@@ -661,7 +803,7 @@ fn parse_fn(
 			t.clone().map(ast::TypeData::Void)
 		}
 	} else {
-		let mut l = tokens.last().unwrap().loc().clone();
+		let mut l = *tokens.last().unwrap().loc();
 		l.end.col += 1;
 		l.start = l.end;
 		let span = Span::new((), l);
@@ -670,7 +812,7 @@ fn parse_fn(
 			ReturnValue::UnclosedParens,
 		));
 	};
-	let body = match match value::parse_block(&tokens, *i, intrinsics) {
+	let body = match match parse_block(&tokens, *i, intrinsics, errors) {
 		Ok(v) => v,
 		Err(e) => return Err(e),
 	} {
@@ -679,7 +821,7 @@ fn parse_fn(
 			v
 		}
 		None => {
-			let mut l = tokens.last().unwrap().loc().clone();
+			let mut l = *tokens.last().unwrap().loc();
 			l.end.col += 1;
 			l.start = l.end;
 			let span = Span::new((), l);
@@ -691,7 +833,7 @@ fn parse_fn(
 	};
 	Ok(Span::join(
 		&[ident.clone(), body.clone().map(String::new())],
-		ast::Item::Fn(ident, args, ret_type, body),
+		ast::Item::Fn(ident, args, ret_type, body, attrs),
 	))
 }
 
@@ -700,19 +842,24 @@ fn parse_type(tokens: &[Span<Token>], index: usize) -> Result<(Span<ast::Type>,
 		if let Token::Ident(i) = t.val() {
 			let typ = match i.as_str() {
 				"number" => ast::TypeData::Number.default_type(),
+				"integer" => ast::TypeData::Integer.default_type(),
+				"bigint" => ast::TypeData::BigInt.default_type(),
+				"char" => ast::TypeData::Char.default_type(),
 				"string" => ast::TypeData::String.default_type(),
 				"void" => ast::TypeData::Void.default_type(),
 				"bool" => ast::TypeData::Bool.default_type(),
 				_ => ast::TypeData::Other(i).default_type(),
 			};
 			return Ok((t.clone().map(typ), 1));
-		} else if matches!(t.val(), Token::Kwd(k) if k == "[") {
+		} else if matches!(t.as_ref(), Token::Bang) {
+			return Ok((t.clone().map(ast::TypeData::Never.default_type()), 1));
+		} else if matches!(t.as_ref(), Token::LBracket) {
 			let (s, offset) = match parse_type(tokens, index + 1) {
 				Ok((r, offset)) => (r.clone().map(r.as_ref().type_data().clone()), offset),
 				Err(e) => return Err(e),
 			};
 			if let Some(t_end) = tokens.get(index + offset + 1) {
-				if matches!(t_end.val(), Token::Kwd(k) if k == "]") {
+				if matches!(t_end.as_ref(), Token::RBracket) {
 					return Ok((
 						t.clone().join_with(
 							&[t_end.clone()],
@@ -724,7 +871,7 @@ fn parse_type(tokens: &[Span<Token>], index: usize) -> Result<(Span<ast::Type>,
 					return Err(t_end.error("Expected `]`", ReturnValue::UnclosedBracket));
 				}
 			} else {
-				let mut l = tokens.last().unwrap().loc().clone();
+				let mut l = *tokens.last().unwrap().loc();
 				l.end.col += 1;
 				l.start = l.end;
 				let span = Span::new((), l);
@@ -736,7 +883,7 @@ fn parse_type(tokens: &[Span<Token>], index: usize) -> Result<(Span<ast::Type>,
 			return Err(t.error("Expected type", ReturnValue::UnexpectedNonIdentifier));
 		}
 	} else {
-		let mut l = tokens.last().unwrap().loc().clone();
+		let mut l = *tokens.last().unwrap().loc();
 		l.end.col += 1;
 		l.start = l.end;
 		let span = Span::new((), l);
@@ -744,395 +891,320 @@ fn parse_type(tokens: &[Span<Token>], index: usize) -> Result<(Span<ast::Type>,
 	};
 }
 
-/// Parse an expression
-pub fn parse_expr(
+/// Parse an expression that spans all the `tokens`
+pub fn parse_expr(tokens: &[Span<Token>], intrinsics: bool) -> Result<Span<ast::Expr>, Error> {
+	let mut i = 0;
+	let mut errors = Vec::new();
+	let expr = parse_expr_with_priority(tokens, &mut i, 0, intrinsics, true, &mut errors)?;
+	if !errors.is_empty() {
+		return Err(errors.remove(0));
+	}
+	skip_eol(tokens, &mut i);
+	if let Some(t) = tokens.get(i) {
+		return Err(t.error(format!("Unexpected `{}`", t.as_ref()), ReturnValue::UnexpectedToken));
+	}
+	Ok(expr)
+}
+
+fn skip_eol(tokens: &[Span<Token>], i: &mut usize) {
+	while let Some(Token::EOL) = tokens.get(*i).map(|x| x.as_ref()) {
+		*i += 1;
+	}
+}
+
+/// A location going from the start of `start` to the end of `end`
+fn loc_between(start: &impl HasLoc, end: &impl HasLoc) -> Location {
+	Location::new(start.loc().file(), start.loc().start, end.loc().end)
+}
+
+/// Error for running out of tokens while expecting `expected`
+fn eoi_error(tokens: &[Span<Token>], expected: &str) -> Error {
+	let mut l = *tokens.last().unwrap().loc();
+	l.end.col += 1;
+	l.start = l.end;
+	let span = Span::new((), l);
+	span.error(format!("Unexpected EOI, expected {}", expected), ReturnValue::UnclosedParens)
+}
+
+/// Pratt parser, parses an expression whose operators all have at least `min_priority`.
+/// Inside parenthesis (`nested`) expressions can span multiple lines, on a statement an end of line ends the expression
+fn parse_expr_with_priority(
 	tokens: &[Span<Token>],
+	i: &mut usize,
+	min_priority: u8,
 	intrinsics: bool,
-	last: usize,
+	nested: bool,
+	errors: &mut Vec<Error>,
 ) -> Result<Span<ast::Expr>, Error> {
-	let len = tokens.len();
-	match parse_expr_with_priority(&tokens, 0, 0, intrinsics) {
-		Ok((v, offset)) => {
-			//println!("{:?}", tokens);
-			//println!("{:?}", v);
-			//assert_eq!(offset, len + last, "Didn't parse all the tokens");
-			if offset != len + last {
-				if offset+1 < len {
-					return Err(tokens[offset+1].error("Unexpected token", ReturnValue::UnexpectedToken))
-				}else{
-					panic!("Offset is greater than token len, ¯\\_(ツ)_/¯")
-				}
+	let mut lhs = parse_prefix(tokens, i, intrinsics, nested, errors)?;
+	loop {
+		if nested {
+			skip_eol(tokens, i);
+		}
+		let tok = match tokens.get(*i) {
+			Some(t) => t,
+			None => break,
+		};
+		if let Ok(op) = PostfixOperator::try_from(tok.as_ref()) {
+			if op.priority() < min_priority {
+				break;
 			}
-			Ok(v)
+			*i += 1;
+			lhs = match op {
+				PostfixOperator::Call => {
+					let mut args = Vec::new();
+					loop {
+						skip_eol(tokens, i);
+						match tokens.get(*i).map(|x| x.as_ref()) {
+							Some(Token::RParen) => break,
+							Some(_) => (),
+							None => return Err(eoi_error(tokens, "`)`")),
+						}
+						args.push(parse_expr_with_priority(tokens, i, 0, intrinsics, true, errors)?);
+						skip_eol(tokens, i);
+						match tokens.get(*i) {
+							Some(t) if matches!(t.as_ref(), Token::Comma) => *i += 1,
+							Some(t) if matches!(t.as_ref(), Token::RParen) => (),
+							Some(t) => return Err(t.error("Expected `,` or `)`", ReturnValue::UnexpectedToken)),
+							None => return Err(eoi_error(tokens, "`)`")),
+						}
+					}
+					*i += 1;
+					let loc = loc_between(&lhs, &tokens[*i - 1]);
+					Span::new(ast::Expr::Call(Box::new(lhs), args), loc)
+				}
+				PostfixOperator::As => {
+					let (typ, off) = parse_type(tokens, *i)?;
+					*i += off;
+					let typ = typ.clone().map(typ.as_ref().type_data().clone());
+					let loc = loc_between(&lhs, &typ);
+					Span::new(ast::Expr::Cast(Box::new(lhs), typ), loc)
+				}
+			};
+			continue;
+		}
+		let op = match Operator::try_from(tok.as_ref()) {
+			Ok(op) => op,
+			Err(_) => break,
+		};
+		let (left_priority, right_priority) = op.binding_power();
+		if left_priority < min_priority {
+			break;
 		}
-		Err(e) => Err(e),
+		*i += 1;
+		let rhs = parse_expr_with_priority(tokens, i, right_priority, intrinsics, nested, errors)?;
+		let loc = loc_between(&lhs, &rhs);
+		lhs = Span::new(ast::Expr::from_op(op, lhs, rhs), loc);
 	}
+	Ok(lhs)
 }
 
-fn parse_expr_with_priority(
+/// Parses a value, or a prefix operator applied to an expression
+fn parse_prefix(
 	tokens: &[Span<Token>],
-	index: usize,
-	priority: u8,
+	i: &mut usize,
 	intrinsics: bool,
-) -> Result<(Span<ast::Expr>, usize), Error> {
-	let mut offset = 0;
-	let mut rhs = match next_value(&tokens, index, intrinsics) {
-		Ok(v) => v,
-		Err(e) => return Err(e),
+	nested: bool,
+	errors: &mut Vec<Error>,
+) -> Result<Span<ast::Expr>, Error> {
+	// An operand is always needed, so the expression continues on the next line
+	skip_eol(tokens, i);
+	let v = match tokens.get(*i) {
+		Some(v) => v,
+		None => return Err(eoi_error(tokens, "value")),
 	};
-	offset += rhs.1;
-	while let Some(curr_op) = next_op(&tokens, index + offset) {
-		//dbg!(curr_op);
-		if curr_op.0.priority() < priority {
-			break;
+	if let Ok(op) = PrefixOperator::try_from(v.as_ref()) {
+		*i += 1;
+		let operand = parse_expr_with_priority(tokens, i, op.priority(), intrinsics, nested, errors)?;
+		let loc = loc_between(v, &operand);
+		return Ok(match op {
+			PrefixOperator::Neg => Span::new(ast::Expr::Neg(Box::new(operand)), loc),
+		});
+	}
+	if let Some((block, off)) = parse_block(tokens, *i, intrinsics, errors)? {
+		*i += off;
+		return Ok(block);
+	}
+	let expr = match v.as_ref() {
+		Token::LParen => {
+			*i += 1;
+			let inner = parse_expr_with_priority(tokens, i, 0, intrinsics, true, errors)?;
+			skip_eol(tokens, i);
+			match tokens.get(*i) {
+				Some(t) if matches!(t.as_ref(), Token::RParen) => (),
+				Some(t) => return Err(t.error("Expected `)`", ReturnValue::UnclosedParens)),
+				None => return Err(eoi_error(tokens, "`)`")),
+			}
+			inner
 		}
-		offset += curr_op.1;
-		//println!("Index: {} + {} ({:?})", index, offset, curr_op.0);
-		let mut lhs = match next_value(&tokens, index + offset, intrinsics) {
-			Ok(v) => v,
-			Err(e) => return Err(e),
-		};
-
-		if let Some(next_op) = next_op(&tokens, index + offset) {
-			//dbg!(next_op);
-			if next_op.0.priority() > curr_op.0.priority() {
-				lhs = match parse_expr_with_priority(
-					&tokens,
-					index + offset,
-					next_op.0.priority(),
-					intrinsics,
-				) {
-					Ok(v) => v,
-					Err(e) => return Err(e),
+		Token::Number(n) => v.clone().map(ast::Expr::Value(ast::Value::Num(*n))),
+		Token::BigInt(n) => v.clone().map(ast::Expr::Value(ast::Value::BigInt(n.clone()))),
+		Token::True => v.clone().map(ast::Expr::Value(ast::Value::True)),
+		Token::False => v.clone().map(ast::Expr::Value(ast::Value::False)),
+		Token::Ident(id) => {
+			if intrinsics && id.starts_with("INTRINSIC_") {
+				if let Some(i) = ast::intrinsics::Intrinsic::from_str(&id) {
+					v.clone().map(ast::Expr::CompilerIntrinsic(i))
+				} else {
+					return Err(v.error("Intrinsic not defined", ReturnValue::IntrinsicNotDefined));
 				}
+			} else {
+				v.clone().map(ast::Expr::Ident(id.clone()))
 			}
 		}
-		offset += lhs.1;
-		if offset == tokens.len() {
-			rhs = (
-				rhs.0.clone().join_with(
-					&[lhs.0.clone()],
-					ast::Expr::from_op(curr_op.0, rhs.0, lhs.0),
-				),
-				offset,
-			);
-			break;
-		}
-		if curr_op.0.priority() == priority {
-			rhs = (
-				rhs.0.clone().join_with(
-					&[lhs.0.clone()],
-					ast::Expr::from_op(curr_op.0, rhs.0, lhs.0),
-				),
-				offset,
-			);
-			continue;
+		Token::String(s) => v.clone().map(ast::Expr::Value(ast::Value::Str(s.clone()))),
+		Token::Char(c) => v.clone().map(ast::Expr::Value(ast::Value::Char(*c))),
+		Token::InterpolatedString(parts) => {
+			let mut exprs = Vec::with_capacity(parts.len());
+			for part in parts {
+				exprs.push(match part {
+					StringPart::Str(s) => v.clone().map(ast::Expr::Value(ast::Value::Str(s.clone()))),
+					StringPart::Expr(tokens) => {
+						let mut idx = 0;
+						let e = parse_expr_with_priority(tokens, &mut idx, 0, intrinsics, true, errors)?;
+						skip_eol(tokens, &mut idx);
+						if let Some(t) = tokens.get(idx) {
+							return Err(t.error(format!("Unexpected `{}`", t.as_ref()), ReturnValue::UnexpectedToken));
+						}
+						e.clone().map(ast::Expr::Display(Box::new(e)))
+					}
+				})
+			}
+			// "a {x} b" is desugared into ("a " + x.to_string()) + " b"
+			let mut exprs = exprs.into_iter();
+			let first = exprs.next().unwrap();
+			exprs.fold(first, |acc, e| v.clone().map(ast::Expr::Add(Box::new(acc), Box::new(e))))
 		}
-		if curr_op.0.priority() > priority {
-			rhs = match parse_expr_with_priority(&tokens, index, curr_op.0.priority(), intrinsics) {
-				Ok(v) => v,
-				Err(e) => return Err(e),
-			};
-			continue;
+		x => {
+			return Err(v.error(format!("Expected value, not `{}`", x), ReturnValue::UnexpectedNonValue))
 		}
-	}
-	return Ok(rhs);
+	};
+	*i += 1;
+	Ok(expr)
 }
 
-fn next_op(tokens: &[Span<Token>], index: usize) -> Option<(Operator, usize)> {
-	//println!("Next op: {:?}", tokens.get(index..));
-	// if index >= tokens.len() {
-	// 	return None;
-	// }
-	let mut i = 0;
-	while index + i < tokens.len() {
-		if let Some(Token::Kwd(c)) = tokens.get(i + index).map(|x| x.val()) {
-			if let Ok(op) = Operator::try_from(c.as_str()) {
-				//println!("[{}] = {}", i + index, c);
-				return Some((op, i + 1));
+/// Parses a block in a single pass, statements end with a `;`, and an expression at the end of a line is returned.
+/// A statement with an error is recorded in `errors` and skipped, parsing continues on the next statement
+pub fn parse_block(
+	tokens: &[Span<Token>],
+	index: usize,
+	intrinsics: bool,
+	errors: &mut Vec<Error>,
+) -> Result<Option<(Span<ast::Expr>, usize)>, Error> {
+	if !matches!(tokens.get(index).map(|x| x.as_ref()), Some(Token::LBrace)) {
+		return Ok(None);
+	}
+	let mut i = index + 1;
+	let mut body = Vec::new();
+	loop {
+		skip_eol(tokens, &mut i);
+		match tokens.get(i).map(|x| x.as_ref()) {
+			Some(Token::RBrace) => break,
+			Some(_) => (),
+			None => return Err(eoi_error(tokens, "`}`")),
+		}
+		let expr = match parse_expr_with_priority(tokens, &mut i, 0, intrinsics, false, errors) {
+			Ok(expr) => expr,
+			Err(e) => {
+				errors.push(e);
+				skip_statement(tokens, &mut i);
+				continue;
 			}
 		};
-		i += 1;
+		match tokens.get(i) {
+			Some(t) if matches!(t.as_ref(), Token::Semicolon) => {
+				body.push(expr);
+				i += 1;
+			}
+			Some(t) if matches!(t.as_ref(), Token::EOL | Token::RBrace) => {
+				let loc = *expr.loc();
+				body.push(Span::new(ast::Expr::Return(Box::new(expr)), loc));
+			}
+			Some(t) => {
+				let e = t.error(format!("Unexpected `{}`, expected `;`", t.as_ref()), ReturnValue::ExpectedSemicolon);
+				// A name alone does nothing, so a name followed by another statement is more likely a keyword
+				// this language doesn't have, like `let x = 1`
+				errors.push(if starts_expr(t.as_ref()) && !matches!(expr.as_ref(), ast::Expr::Ident(_)) {
+					e.fix("Add a `;`", Fix::InsertAfter(*expr.loc(), ";".into()))
+				} else {
+					e.help("A statement ends with a `;` or at the end of the line")
+				});
+				skip_statement(tokens, &mut i);
+			}
+			None => return Err(eoi_error(tokens, "`}`")),
+		}
 	}
-	return None;
+	let loc = loc_between(&tokens[index], &tokens[i]);
+	Ok(Some((Span::new(ast::Expr::Block(body), loc), i + 1 - index)))
 }
 
-pub use value::next_value;
-
-mod value {
-	use super::{ast, parse_expr_with_priority, Error, ReturnValue, Span, SpanError, Token};
-	use crate::{span::HasLoc, RecursiveFn};
+/// Whether an expression can start with `t`, so a `;` before it would end the statement before a new one
+fn starts_expr(t: &Token) -> bool {
+	PrefixOperator::try_from(t).is_ok()
+		|| matches!(
+			t,
+			Token::LParen
+				| Token::LBrace
+				| Token::Number(_)
+				| Token::BigInt(_)
+				| Token::True
+				| Token::False
+				| Token::Ident(_)
+				| Token::String(_)
+				| Token::InterpolatedString(_)
+				| Token::Char(_)
+		)
+}
 
-	enum State {
-		LhsUnary,
-		Central,
-		RhsUnary,
+/// Skips the tokens up to the end of the current statement: a `;` or the end of the line, or the `}` closing the block.
+/// Used to continue parsing a block after an error
+fn skip_statement(tokens: &[Span<Token>], i: &mut usize) {
+	let mut depth = 0;
+	while let Some(t) = tokens.get(*i) {
+		match t.as_ref() {
+			Token::LParen | Token::LBrace | Token::LBracket => depth += 1,
+			Token::RParen | Token::RBracket if depth > 0 => depth -= 1,
+			Token::RBrace if depth > 0 => depth -= 1,
+			Token::RBrace => return,
+			Token::Semicolon if depth == 0 => {
+				*i += 1;
+				return;
+			}
+			Token::EOL if depth == 0 => return,
+			_ => (),
+		}
+		*i += 1;
 	}
+}
 
-	pub fn next_value(
-		tokens: &[Span<Token>],
-		index: usize,
-		intrinsics: bool,
-	) -> Result<(Span<ast::Expr>, usize), Error> {
-		//println!("VALUE: {:?}", tokens.get(index..));
-		let mut offset = 0;
-		let mut state = State::LhsUnary;
-		let mut neg = RecursiveFn::new(|x: Span<ast::Expr>| {
-			let mut loc = x.loc().clone();
-			loc.start.col -= 1;
-			Span::new(ast::Expr::Neg(Box::new(x)), loc)
-		});
-		let mut central = None;
-		'main: loop {
-			match state {
-				State::LhsUnary => {
-					while let Some(Token::Kwd(x)) = tokens.get(index + offset).map(|x| x.val()) {
-						//matches!(tokens.get(index + offset), Some(Token::Other(x)) if x == &'-' || x == &'!') {
-
-						match x.as_str() {
-							"-" => neg.add(),
-							_ => break,
-						};
-						offset += 1;
-					}
-					state = State::Central;
-				}
-				State::Central => {
-					//println!("Central: {:?}", &tokens.get(index + offset..));
-					if let Some((expr, off)) =
-						match parse_block(&tokens, index + offset, intrinsics) {
-							Ok(v) => v,
-							Err(e) => return Err(e),
-						} {
-						central = Some(expr);
-						offset += off;
-					} else if matches!(tokens.get(index + offset).map(|x| x.val()), Some(Token::Kwd(x)) if x == "(")
-					{
-						// if true {
-						offset += 1;
-						let mut count = 0;
-						let mut inner_tokens = Vec::new();
-						loop {
-							if index + offset >= tokens.len() {
-								let mut l = tokens.last().unwrap().loc().clone();
-								l.end.col += 1;
-								l.start = l.end;
-								let span = Span::new((), l);
-								return Err(span.error(
-									"Unexpected EOI, expected `)`",
-									ReturnValue::UnclosedParens,
-								));
-								//panic!("Unexpected EOI")
-							}
-							if matches!(
-								tokens.get(index + offset).map(|x| x.val()),
-								Some(Token::EOL)
-							) {
-								offset += 1;
-							}
-							// println!("{:?}", tokens[index + offset]);
-							if matches!(tokens[index + offset].clone().val(), Token::Kwd(x) if x == "(")
-							{
-								count += 1;
-							} else if matches!(tokens[index + offset].clone().val(), Token::Kwd(x) if x == ")")
-							{
-								if count == 0 {
-									break;
-								}
-								count -= 1;
-							}
-							inner_tokens.push(tokens[index + offset].clone());
-							offset += 1;
-						}
-						central = Some(match super::parse_expr(&inner_tokens, intrinsics, 0) {
-							Ok(v) => v,
-							Err(e) => return Err(e),
-						});
-					} else {
-						//println!("TOKENS: {:?}", tokens);
-						central = Some(match tokens.get(index + offset) {
-							Some(v) => match v.val() {
-								Token::Number(n) => {
-									v.clone().map(ast::Expr::Value(ast::Value::Num(n)))
-								}
-								Token::Ident(id) => {
-									if id == "true" {
-										v.clone().map(ast::Expr::Value(ast::Value::True))
-									}else if id == "false" {
-										v.clone().map(ast::Expr::Value(ast::Value::False))
-									}else if intrinsics && id.starts_with("INTRINSIC_") {
-										if let Some(i) = ast::intrinsics::Intrinsic::from_str(&id) {
-											v.clone().map(ast::Expr::CompilerIntrinsic(i))
-										} else {
-											return Err(v.error(
-												"Intrinsic not defined",
-												ReturnValue::IntrinsicNotDefined,
-											));
-										}
-									} else {
-										v.clone().map(ast::Expr::Ident(id))
-									}
-								}
-								Token::String(s) => {
-									v.clone().map(ast::Expr::Value(ast::Value::Str(s)))
-								}
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::source_map::SourceMap;
+	use crate::tokens::tokenize;
 
-								x => {
-									return Err(v.error(
-										format!("Expected value, not {:?}", x),
-										ReturnValue::UnexpectedNonValue,
-									))
-								}
-							},
-							None => {
-								let mut l = tokens.last().unwrap().loc().clone();
-								l.end.col += 1;
-								l.start = l.end;
-								let span = Span::new((), l);
-								return Err(span.error(
-									"Unexpected EOI, expected value",
-									ReturnValue::UnclosedParens,
-								));
-							}
-						});
-					}
-					offset += 1;
-					state = State::RhsUnary;
-				}
-				State::RhsUnary => {
-					while matches!(tokens.get(index + offset).map(|x|x.val()), Some(Token::Kwd(x)) if x == "(")
-					{
-						offset += 1;
-						let mut count = 0;
-						let mut inner_tokens: Vec<Span<Token>> = Vec::new();
-						loop {
-							if index + offset >= tokens.len() {
-								let mut l = tokens.last().unwrap().loc().clone();
-								l.end.col += 1;
-								l.start = l.end;
-								let span = Span::new((), l);
-								return Err(span.error(
-									"Unexpected EOI, expected `)`",
-									ReturnValue::UnclosedParens,
-								));
-							}
-							if matches!(
-								tokens.get(index + offset).map(|x| x.val()),
-								Some(Token::EOL)
-							) {
-								offset += 1;
-							}
-							if matches!(tokens.get(index + offset).map(|x|x.val()), Some(Token::Kwd(x)) if x == "(")
-							{
-								count += 1;
-							} else if matches!(tokens.get(index + offset).map(|x|x.val()), Some(Token::Kwd(x)) if x == ")")
-							{
-								if count == 0 {
-									break;
-								}
-								count -= 1;
-							}
-							inner_tokens.push(tokens[index + offset].clone());
-							offset += 1;
-						}
-						offset += 1;
-						let i: Vec<Result<Span<ast::Expr>, Error>> = inner_tokens
-							.split(|x| x.val() == Token::Comma)
-							.filter(|x| !x.is_empty())
-							.map(|x| super::parse_expr(x.into(), intrinsics, 0))
-							.collect();
-						let mut i_unwrapped = Vec::with_capacity(i.len());
-						for x in i {
-							match x {
-								Ok(v) => i_unwrapped.push(v),
-								Err(e) => return Err(e),
-							}
-						}
-						central = central.map(|x| {
-							x.clone().join_with(
-								&i_unwrapped.clone(),
-								ast::Expr::Call(Box::new(x), i_unwrapped),
-							)
-						})
-					}
-					break 'main;
-				}
-			}
-			//offset += 1;
-		}
-		central = central.map(|x| neg.call(x));
-		//println!("Result: {:?}", central);
-		Ok((central.unwrap(), offset))
+	/// The expression with every operation in parenthesis
+	fn grouped(s: &str) -> String {
+		let mut source_map = SourceMap::new();
+		let file = source_map.add("test.lang".into(), s.to_string());
+		let tokens = tokenize(file, &source_map).expect("Error tokenizing");
+		parse_expr(&tokens, false).expect("Error parsing").unwrap().to_string()
 	}
 
-	pub fn parse_block(
-		tokens: &[Span<Token>],
-		index: usize,
-		intrinsics: bool,
-	) -> Result<Option<(Span<ast::Expr>, usize)>, Error> {
-		let mut offset = 0;
-		if matches!(tokens.get(index + offset).map(|x| x.val()), Some(Token::Kwd(x)) if x == "{") {
-			// println!("Parsing block: {:?}", &tokens[index..]);
-			// if true {
-			offset += 1;
-			let mut idx = offset + index;
-			let inner_tokens = super::parse_inner(&mut idx, &tokens, "{", "}")?;
-			offset = idx - index;
-			let mut body = Vec::new();
-			let mut inner_offset = 0;
-			while inner_offset < inner_tokens.len() {
-				let next_colon = inner_tokens.iter().enumerate().position(|(i, x)| {
-					// println!("[{}] = {:?}", i,x);
-					i > inner_offset && x.val() == Token::Colon
-				});
-				let next_tokens = if let Some(pos) = next_colon {
-					&inner_tokens[..pos]
-				} else {
-					&inner_tokens
-				};
+	#[test]
+	fn associativity() {
+		assert_eq!(grouped("2 ** 3 ** 2"), "(2 ** (3 ** 2))");
+		assert_eq!(grouped("2 - 3 - 4"), "((2 - 3) - 4)");
+		assert_eq!(grouped("8 / 4 * 2"), "((8 / 4) * 2)");
+	}
 
-				// println!("-> {:?}", next_tokens);
-				while let Some(Token::EOL) = next_tokens.get(inner_offset).map(|x| x.val()) {
-					inner_offset += 1;
-				}
-				let (expr, i) =
-					match parse_expr_with_priority(next_tokens, inner_offset, 0, intrinsics) {
-						Ok(v) => v,
-						Err(e) => return Err(e),
-					};
-				inner_offset += i;
-				// println!("{:?}", inner_tokens.get(inner_offset));
-				if matches!(
-					inner_tokens.get(inner_offset).map(|x| x.val()),
-					Some(Token::EOL) | None
-				) {
-					// println!("Pushing return {}", expr);
-					body.push(expr.clone().map(ast::Expr::Return(Box::new(expr))));
-					inner_offset += 1;
-				} else if matches!(
-					inner_tokens.get(inner_offset).map(|x| x.val()),
-					Some(Token::Colon)
-				) {
-					body.push(expr);
-					inner_offset += 1;
-					if matches!(
-						inner_tokens.get(inner_offset).map(|x| x.val()),
-						Some(Token::EOL)
-					) {
-						inner_offset += 1;
-						// println!("> {:?}", inner_tokens.get(inner_offset));
-					}
-				}
-			}
-			body.reverse();
-			// println!("{:?}", body);
-			return Ok(Some((
-				Span::join(&tokens[..index + offset], ast::Expr::Block(body)),
-				offset + 1,
-			)));
-		// end
-		} else {
-			return Ok(None);
-		}
+	#[test]
+	fn precedence() {
+		assert_eq!(grouped("-2 ** 2"), "-(2 ** 2)");
+		assert_eq!(grouped("-2 * 2"), "(-2 * 2)");
+		assert_eq!(grouped("-x as integer"), "(-x as integer)");
+		assert_eq!(grouped("a as integer + b"), "((a as integer) + b)");
+		assert_eq!(grouped("a * b as integer"), "(a * (b as integer))");
+		assert_eq!(grouped("1 + 2 * 3 == 7"), "((1 + (2 * 3)) == 7)");
 	}
 }
@@ -0,0 +1,280 @@
+//! Name resolution: every binding gets a unique symbol and every identifier is resolved to the symbol it refers to
+use crate::ast::{Expr, Ident, Item, TypeData};
+use crate::error::{Error, ReturnValue};
+use crate::span::location::Location;
+use crate::span::{HasLoc, Span, SpanError};
+
+use std::collections::{HashMap, HashSet};
+
+/// Identifies a binding, unique in the whole program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymbolId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+	Fn,
+	Arg,
+	Local,
+	LocalMut,
+	Trait,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+	pub name: Ident,
+	pub kind: SymbolKind,
+	/// Location of the name where the symbol is defined
+	pub def: Location,
+	/// Symbol with the same name hidden by this one
+	pub shadows: Option<SymbolId>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+	symbols: Vec<Symbol>,
+	/// Symbol defined at each location
+	definitions: HashMap<Location, SymbolId>,
+	/// Symbol each identifier refers to, by the location of the identifier
+	references: HashMap<Location, SymbolId>,
+	/// Arguments of the functions implemented by an intrinsic, the intrinsic reads them
+	intrinsic_args: HashSet<SymbolId>,
+}
+
+#[allow(unused)]
+impl SymbolTable {
+	pub fn get(&self, id: SymbolId) -> &Symbol {
+		&self.symbols[id.0]
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (SymbolId, &Symbol)> {
+		self.symbols.iter().enumerate().map(|(i, s)| (SymbolId(i), s))
+	}
+
+	/// Symbol defined at `loc`
+	pub fn definition(&self, loc: &Location) -> Option<SymbolId> {
+		self.definitions.get(loc).copied()
+	}
+
+	/// Symbol the identifier at `loc` refers to
+	pub fn reference(&self, loc: &Location) -> Option<SymbolId> {
+		self.references.get(loc).copied()
+	}
+
+	/// Symbol defined or referred to at `loc`, its definition is at `get(id).def`
+	pub fn symbol_at(&self, loc: &Location) -> Option<SymbolId> {
+		self.definition(loc).or_else(|| self.reference(loc))
+	}
+
+	/// Locations of the identifiers that refer to `id`
+	pub fn references_to(&self, id: SymbolId) -> impl Iterator<Item = &Location> {
+		self.references
+			.iter()
+			.filter(move |(_, s)| **s == id)
+			.map(|(loc, _)| loc)
+	}
+
+	/// Symbols referred to at least once
+	pub fn used(&self) -> HashSet<SymbolId> {
+		self.references
+			.values()
+			.chain(&self.intrinsic_args)
+			.copied()
+			.collect()
+	}
+
+	/// Symbols that hide another one with the same name
+	pub fn shadowing(&self) -> impl Iterator<Item = (SymbolId, &Symbol)> {
+		self.iter().filter(|(_, s)| s.shadows.is_some())
+	}
+
+	fn add(&mut self, symbol: Symbol) -> SymbolId {
+		let id = SymbolId(self.symbols.len());
+		self.definitions.insert(symbol.def, id);
+		self.symbols.push(symbol);
+		id
+	}
+}
+
+/// Names visible in a block or function
+#[derive(Default)]
+struct Frame {
+	names: HashMap<Ident, SymbolId>,
+	/// Names defined later in the block, using them now is an error
+	later: HashSet<Ident>,
+}
+
+pub struct Resolver {
+	table: SymbolTable,
+	/// The first frame has the items, the last one is the innermost block
+	frames: Vec<Frame>,
+	/// Traits are named apart from the other items
+	traits: HashMap<Ident, SymbolId>,
+	/// Arguments of the function being resolved
+	fn_args: Vec<SymbolId>,
+}
+
+impl Default for Resolver {
+	fn default() -> Self {
+		Self {
+			table: SymbolTable::default(),
+			frames: vec![Frame::default()],
+			traits: HashMap::new(),
+			fn_args: Vec::new(),
+		}
+	}
+}
+
+impl Resolver {
+	pub fn table(&self) -> &SymbolTable {
+		&self.table
+	}
+
+	pub fn into_table(self) -> SymbolTable {
+		self.table
+	}
+
+	/// Adds the functions and traits to the items, and resolves the traits that are implemented.
+	/// The ones already defined are left to the checker to report
+	pub fn declare_items(&mut self, items: &[Span<Item>]) {
+		for item in items {
+			let (name, kind, names) = match item.as_ref() {
+				Item::Fn(name, _, _, _, _) => (name, SymbolKind::Fn, &mut self.frames[0].names),
+				Item::TraitDef(name, _, _) => (name, SymbolKind::Trait, &mut self.traits),
+				Item::ImplTrait(_, _, _) => continue,
+			};
+			if !names.contains_key(name.as_ref()) {
+				let id = self.table.add(Symbol {
+					name: name.val(),
+					kind,
+					def: *name.loc(),
+					shadows: None,
+				});
+				names.insert(name.val(), id);
+			}
+		}
+		for item in items {
+			if let Item::ImplTrait(_, impl_trait, _) = item.as_ref() {
+				let trait_name = impl_trait.trait_name_span();
+				if let Some(id) = self.traits.get(trait_name.as_ref()) {
+					self.table.references.insert(*trait_name.loc(), *id);
+				}
+			}
+		}
+	}
+
+	/// Resolves the body of a function, returns the symbols of its arguments
+	pub fn resolve_fn(
+		&mut self,
+		args: &[(Span<Ident>, Span<TypeData>)],
+		body: &Span<Expr>,
+		errors: &mut Vec<Error>,
+	) -> Vec<SymbolId> {
+		self.frames.push(Frame::default());
+		let args = args
+			.iter()
+			.map(|(name, _)| self.define(name.as_ref(), SymbolKind::Arg, name.loc(), errors))
+			.collect::<Vec<SymbolId>>();
+		let outer_args = std::mem::replace(&mut self.fn_args, args.clone());
+		self.resolve_expr(body, errors);
+		self.fn_args = outer_args;
+		self.frames.pop();
+		args
+	}
+
+	pub fn resolve_expr(&mut self, expr: &Span<Expr>, errors: &mut Vec<Error>) {
+		match expr.as_ref() {
+			Expr::Block(b) => {
+				let later = b
+					.iter()
+					.filter_map(|e| match e.as_ref() {
+						Expr::Define(id, _) | Expr::DefineMut(id, _) => Some(id.clone()),
+						_ => None,
+					})
+					.collect();
+				self.frames.push(Frame { names: HashMap::new(), later });
+				for e in b {
+					self.resolve_expr(e, errors);
+				}
+				self.frames.pop();
+			}
+			Expr::Define(id, value) | Expr::DefineMut(id, value) => {
+				// The value can't use the name it defines
+				self.resolve_expr(value, errors);
+				let kind = if let Expr::DefineMut(_, _) = expr.as_ref() {
+					SymbolKind::LocalMut
+				} else {
+					SymbolKind::Local
+				};
+				self.define(id, kind, expr.loc(), errors);
+				self.frames.last_mut().unwrap().later.remove(id);
+			}
+			Expr::Ident(id) => match self.lookup(id) {
+				Some(symbol) => {
+					self.table.references.insert(*expr.loc(), symbol);
+				}
+				None if self.frames.iter().any(|f| f.later.contains(id)) => errors.push(expr.error(
+					format!("Name `{}` used before its definition", id),
+					ReturnValue::IdentNotDefined,
+				)),
+				None => {
+					let visible = self.frames.iter().flat_map(|f| f.names.keys().map(String::as_str));
+					errors.push(
+						expr.error(format!("Name `{}` not defined", id), ReturnValue::IdentNotDefined)
+							.suggest_similar(id, visible, expr.loc()),
+					)
+				}
+			},
+			Expr::Call(callee, args) => {
+				self.resolve_expr(callee, errors);
+				for arg in args {
+					self.resolve_expr(arg, errors);
+				}
+			}
+			Expr::Return(e) | Expr::Neg(e) | Expr::Display(e) | Expr::Cast(e, _) => {
+				self.resolve_expr(e, errors)
+			}
+			Expr::Add(lhs, rhs)
+			| Expr::Sub(lhs, rhs)
+			| Expr::Mul(lhs, rhs)
+			| Expr::Div(lhs, rhs)
+			| Expr::Exp(lhs, rhs)
+			| Expr::Eq(lhs, rhs)
+			| Expr::Lt(lhs, rhs)
+			| Expr::Le(lhs, rhs)
+			| Expr::Gt(lhs, rhs)
+			| Expr::Ge(lhs, rhs) => {
+				self.resolve_expr(lhs, errors);
+				self.resolve_expr(rhs, errors);
+			}
+			Expr::CompilerIntrinsic(_) => self.table.intrinsic_args.extend(&self.fn_args),
+			Expr::Value(_) | Expr::None => (),
+		}
+	}
+
+	fn lookup(&self, name: &str) -> Option<SymbolId> {
+		self.frames.iter().rev().find_map(|f| f.names.get(name).copied())
+	}
+
+	/// Adds a symbol to the innermost frame, the name is only bound if it isn't already in that frame
+	fn define(&mut self, name: &Ident, kind: SymbolKind, def: &Location, errors: &mut Vec<Error>) -> SymbolId {
+		let shadows = self.lookup(name);
+		let id = self.table.add(Symbol {
+			name: name.clone(),
+			kind,
+			def: *def,
+			shadows,
+		});
+		let frame = self.frames.last_mut().unwrap();
+		if let Some(first) = frame.names.get(name) {
+			errors.push(
+				Span::new((), *def)
+					.error(format!("Name `{}` already defined", name), ReturnValue::NameDefined)
+					.label("Defined again here")
+					.secondary_label(self.table.get(*first).def, "First defined here"),
+			);
+		} else {
+			frame.names.insert(name.clone(), id);
+		}
+		id
+	}
+}
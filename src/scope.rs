@@ -1,6 +1,6 @@
 use crate::ast;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
@@ -55,25 +55,6 @@ impl<T: Clone> Scope<T> {
 		}
 	}
 
-	fn with_parent(parent: Scope<T>) -> Self {
-		Self {
-			parent: Some(Box::new(parent)),
-			variables: HashMap::new(),
-		}
-	}
-
-	pub fn push(self) -> Self {
-		Self::with_parent(self)
-	}
-
-	pub fn pop(self) -> Self {
-		if let Some(p) = self.parent {
-			*p
-		}else{
-			self
-		}
-	}
-
 	// pub fn is_root(&self) -> bool {
 	// 	return self.parent.is_none()
 	// }
@@ -134,17 +115,6 @@ impl<T: Clone> Scope<T> {
 		self.variables.insert(variable, (typ, value));
 		Ok(())
 	}
-
-	pub fn map<U: Clone, F: Fn(T) -> U>(mut self, f: &F) -> Scope<U> {
-		Scope {
-			parent: self.parent.map(|p| Box::new(p.map(f))),
-			variables: self
-				.variables
-				.drain()
-				.map(|(k, (typ, v))| (k, (typ, f(v))))
-				.collect(),
-		}
-	}
 }
 
 impl<T: Clone + std::fmt::Display> std::fmt::Display for Scope<T> {
@@ -261,6 +231,22 @@ impl<K: Hash + Eq, V: Clone> GenericScope<K, V> {
 	// 	}
 	// }
 
+	/// Every value, the ones of inner scopes hide the ones of outer scopes with the same key
+	pub fn values(&self) -> Vec<&V> {
+		let mut keys = HashSet::new();
+		let mut ret = Vec::new();
+		let mut scope = Some(self);
+		while let Some(s) = scope {
+			for (k, v) in &s.variables {
+				if keys.insert(k) {
+					ret.push(v);
+				}
+			}
+			scope = s.parent.as_deref();
+		}
+		ret
+	}
+
 	pub fn add(&mut self, variable: K, value: V) -> Result<(), &V> {
 		if self.variables.contains_key(&variable) {
 			return Err(self.variables.get(&variable).unwrap());
@@ -301,6 +287,8 @@ impl TypeDB {
 	pub fn new() -> Self {
 		let mut scope = GenericScope::root();
 		scope.add(ast::TypeData::Number, ast::TypeData::Number.default_type()).unwrap();
+		scope.add(ast::TypeData::Integer, ast::TypeData::Integer.default_type()).unwrap();
+		scope.add(ast::TypeData::BigInt, ast::TypeData::BigInt.default_type()).unwrap();
 		scope.add(ast::TypeData::String, ast::TypeData::String.default_type()).unwrap();
 		Self(scope)
 	}
@@ -349,6 +337,15 @@ impl TypeDB {
 		self.0.get_mut(data).ok()
 	}
 
+	/// The implementations of the trait `name`, with the type each one is for
+	pub fn implementations(&self, name: &str) -> Vec<(&ast::Type, &ast::ImplTrait)> {
+		self.0
+			.values()
+			.into_iter()
+			.flat_map(|t| t.impl_traits().iter().filter(move |i| i.trait_name_string() == name).map(move |i| (t, i)))
+			.collect()
+	}
+
 	pub fn add(&mut self, data: ast::TypeData) -> Result<(), &ast::Type> {
 		self.0.add(data.clone(), data.default_type())
 	}
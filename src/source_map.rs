@@ -0,0 +1,105 @@
+//! Every file of the program, read once. Locations name their file with a `FileId` instead of its path
+use crate::file_provider::{FileProvider, FileReader};
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A file in the source map, small so every location can have one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId(u32);
+
+pub struct SourceFile {
+	name: PathBuf,
+	contents: String,
+	/// Byte offset where each line starts
+	line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+	fn new(name: PathBuf, contents: String) -> Self {
+		let line_starts = std::iter::once(0)
+			.chain(contents.match_indices('\n').map(|(i, _)| i + 1))
+			.collect();
+		Self {
+			name,
+			contents,
+			line_starts,
+		}
+	}
+
+	pub fn name(&self) -> &Path {
+		&self.name
+	}
+
+	pub fn contents(&self) -> &str {
+		&self.contents
+	}
+
+	/// The line with number `line`, starting at 1, without the trailing whitespace
+	pub fn line(&self, line: usize) -> Option<&str> {
+		let start = *self.line_starts.get(line.checked_sub(1)?)?;
+		let end = self.line_starts.get(line).copied().unwrap_or(self.contents.len());
+		Some(self.contents[start..end].trim_end())
+	}
+}
+
+/// A file that couldn't be read, it has no code for an error to point to
+#[derive(Debug)]
+pub struct ReadError {
+	pub path: PathBuf,
+	pub error: io::Error,
+}
+
+impl Display for ReadError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Error reading file `{}`: {}", self.path.display(), self.error)
+	}
+}
+
+#[derive(Default)]
+pub struct SourceMap {
+	files: Vec<SourceFile>,
+	/// Files loaded from a provider, by the path they were loaded with
+	loaded: HashMap<PathBuf, FileId>,
+}
+
+impl SourceMap {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// The file at `p`, read from `fprov` the first time it's loaded. A file that can't be read isn't added
+	pub fn load<FProv: FileProvider<T>, T: FileReader, P: AsRef<Path>>(
+		&mut self,
+		fprov: &FProv,
+		p: &P,
+	) -> Result<FileId, ReadError> {
+		if let Some(id) = self.loaded.get(p.as_ref()) {
+			return Ok(*id);
+		}
+		let f = fprov.get_file(p).map_err(|error| ReadError {
+			path: p.as_ref().into(),
+			error,
+		})?;
+		let id = self.add(p.as_ref().into(), f.contents().to_string());
+		self.loaded.insert(p.as_ref().into(), id);
+		Ok(id)
+	}
+
+	/// Adds a file that isn't read from a provider, like a line of the repl. It's a new file even if the name is used
+	pub fn add(&mut self, name: PathBuf, contents: String) -> FileId {
+		let id = FileId(self.files.len() as u32);
+		self.files.push(SourceFile::new(name, contents));
+		id
+	}
+
+	pub fn get(&self, id: FileId) -> &SourceFile {
+		&self.files[id.0 as usize]
+	}
+
+	pub fn name(&self, id: FileId) -> &Path {
+		self.get(id).name()
+	}
+}
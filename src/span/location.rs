@@ -1,26 +1,31 @@
-use super::File;
+use crate::source_map::FileId;
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct FilePosition {
 	pub line: usize,
+	/// Column in chars, starting at 1
 	pub col: usize,
+	/// Byte offset from the start of the file
+	pub offset: usize,
 }
 
-impl std::fmt::Display for FilePosition {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-		write!(f, "{}:{}", self.line, self.col)
+impl FilePosition {
+	pub fn new(line: usize, col: usize, offset: usize) -> Self {
+		Self { line, col, offset }
 	}
 }
 
-impl From<(usize, usize)> for FilePosition {
-	fn from((line, col): (usize, usize)) -> Self {
-		Self { line, col }
+impl std::fmt::Display for FilePosition {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+		write!(f, "{}:{}", self.line, self.col)
 	}
 }
 
-#[derive(Clone, PartialEq, Eq)]
+/// Code in a file. `end` is the position of its last char, but `end.offset` is the byte after it,
+/// so the code is the bytes `start.offset..end.offset`
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Location {
-	file: File,
+	file: FileId,
 	pub start: FilePosition,
 	pub end: FilePosition,
 }
@@ -42,9 +47,8 @@ impl PartialOrd for Location {
 }
 
 impl Location {
-	pub fn new<F, S, E>(file: F, start: S, end: E) -> Self
+	pub fn new<S, E>(file: FileId, start: S, end: E) -> Self
 	where
-		F: Into<File>,
 		S: Into<FilePosition>,
 		E: Into<FilePosition>,
 	{
@@ -53,32 +57,36 @@ impl Location {
 		assert!(
 			s <= e,
 			format!(
-				"[{}] Location start ({}) isn't smaller than location end ({})",
-				file.into().display(),
+				"[{:?}] Location start ({}) isn't smaller than location end ({})",
+				file,
 				s,
 				e
 			)
 		);
 		Self {
-			file: file.into(),
+			file,
 			start: s,
 			end: e,
 		}
 	}
 
-	pub fn file(&self) -> &File {
-		&self.file
+	pub fn file(&self) -> FileId {
+		self.file
 	}
-}
 
-impl std::fmt::Debug for Location {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-		write!(f, "{}:{{{}-{}}}", self.file.display(), self.start, self.end)
+	/// The position right after the code
+	pub fn after_end(&self) -> FilePosition {
+		FilePosition::new(self.end.line, self.end.col + 1, self.end.offset)
+	}
+
+	/// Whether `other` is inside of this location
+	pub fn contains(&self, other: &Location) -> bool {
+		self.file == other.file && self.start <= other.start && other.end <= self.end
 	}
 }
 
-impl std::fmt::Display for Location {
+impl std::fmt::Debug for Location {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-		write!(f, "{}:{}", self.file.display(), self.start)
+		write!(f, "{:?}:{{{}-{}}}", self.file, self.start, self.end)
 	}
-}
\ No newline at end of file
+}
@@ -1,16 +1,14 @@
 use crate::ast;
+use crate::hir;
 use crate::error::{Error, ErrorKind};
 use crate::tokens::Token;
 
 use std::convert::{AsMut, AsRef};
 use std::hash::Hash;
-use std::path::PathBuf;
 
 pub mod location;
 use location::*;
 
-pub type File = PathBuf;
-
 #[derive(Clone, PartialEq)]
 pub struct Span<T>
 where
@@ -87,7 +85,7 @@ impl<T: Clone> AsMut<T> for Span<T> {
 
 impl std::fmt::Debug for Span<Token> {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-		write!(f, "{:?}[{}]", self.v, self.loc)
+		write!(f, "{:?}[{:?}]", self.v, self.loc)
 	}
 }
 
@@ -157,6 +155,12 @@ impl std::fmt::Debug for Span<ast::TypeData> {
 	}
 }
 
+impl std::fmt::Debug for Span<hir::Expr> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+		write!(f, "{:?}", self.v)
+	}
+}
+
 impl<T: Hash + Clone> Hash for Span<T> {
 	fn hash<H>(&self, hasher: &mut H)
 	where
@@ -193,15 +197,15 @@ where
 {
 	fn error<S: ToString, R: Into<i32>>(&self, info: S, r: R) -> Error {
 		Error::new(
-			self.loc().clone(),
+			*self.loc(),
 			info.to_string(),
 			ErrorKind::Error(r.into()),
 		)
 	}
 	fn warn<S: ToString>(&self, info: S) -> Error {
-		Error::new(self.loc().clone(), info.to_string(), ErrorKind::Warning)
+		Error::new(*self.loc(), info.to_string(), ErrorKind::Warning)
 	}
 	fn info<S: ToString>(&self, info: S) -> Error {
-		Error::new(self.loc().clone(), info.to_string(), ErrorKind::Info)
+		Error::new(*self.loc(), info.to_string(), ErrorKind::Info)
 	}
 }
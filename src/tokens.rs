@@ -1,62 +1,311 @@
-use crate::span::{File as SpanFile, Span, SpanError};
+use crate::span::{HasLoc, Span, SpanError};
 use crate::span::location::{FilePosition, Location};
-use crate::file_provider::{FileProvider, FileReader};
+use crate::source_map::{FileId, SourceMap};
 use crate::error::{ReturnValue, Error};
 
+use std::iter::Peekable;
+
+use num_bigint::BigInt;
+use unicode_xid::UnicodeXID;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
 	Number(f64),
+	BigInt(BigInt),
 	String(String),
+	/// A string with embedded expressions, `"x = {x}"`
+	InterpolatedString(Vec<StringPart>),
+	Char(char),
 	Ident(String),
+	/// A `///` comment, attached to the item that follows it
+	DocComment(String),
 	EOL,
-	Colon,
+
+	// Keywords
+	Fn,
+	Impl,
+	Trait,
+	Type,
+	For,
+	As,
+	True,
+	False,
+
+	// Punctuation
+	/// `(`
+	LParen,
+	/// `)`
+	RParen,
+	/// `{`
+	LBrace,
+	/// `}`
+	RBrace,
+	/// `[`
+	LBracket,
+	/// `]`
+	RBracket,
+	/// `,`
 	Comma,
+	/// `;`
 	Semicolon,
-	Kwd(String),
+	/// `:`
+	Colon,
+	/// `.`
+	Dot,
+	/// `->`
+	Arrow,
+	/// `+`
+	Plus,
+	/// `-`
+	Minus,
+	/// `*`
+	Star,
+	/// `**`
+	StarStar,
+	/// `/`
+	Slash,
+	/// `%`
+	Percent,
+	/// `!`
+	Bang,
+	/// `=`
+	Assign,
+	/// `==`
+	EqEq,
+	/// `!=`
+	NotEq,
+	/// `<`
+	Lt,
+	/// `<=`
+	Le,
+	/// `>`
+	Gt,
+	/// `>=`
+	Ge,
+	/// `#`, starts an attribute
+	Hash,
+}
+
+impl Token {
+	/// The keyword token for `ident`, if it is one
+	fn keyword(ident: &str) -> Option<Self> {
+		Some(match ident {
+			"fn" => Self::Fn,
+			"impl" => Self::Impl,
+			"trait" => Self::Trait,
+			"type" => Self::Type,
+			"for" => Self::For,
+			"as" => Self::As,
+			"true" => Self::True,
+			"false" => Self::False,
+			_ => return None,
+		})
+	}
+
+	/// The punctuation token written as `punct`, if it is one
+	fn punct(punct: &str) -> Option<Self> {
+		Some(match punct {
+			"(" => Self::LParen,
+			")" => Self::RParen,
+			"{" => Self::LBrace,
+			"}" => Self::RBrace,
+			"[" => Self::LBracket,
+			"]" => Self::RBracket,
+			"," => Self::Comma,
+			";" => Self::Semicolon,
+			":" => Self::Colon,
+			"." => Self::Dot,
+			"->" => Self::Arrow,
+			"+" => Self::Plus,
+			"-" => Self::Minus,
+			"*" => Self::Star,
+			"**" => Self::StarStar,
+			"/" => Self::Slash,
+			"%" => Self::Percent,
+			"!" => Self::Bang,
+			"=" => Self::Assign,
+			"==" => Self::EqEq,
+			"!=" => Self::NotEq,
+			"<" => Self::Lt,
+			"<=" => Self::Le,
+			">" => Self::Gt,
+			">=" => Self::Ge,
+			"#" => Self::Hash,
+			_ => return None,
+		})
+	}
+}
+
+impl std::fmt::Display for Token {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+		match self {
+			Self::Number(n) => write!(f, "{}", n),
+			Self::BigInt(n) => write!(f, "{}n", n),
+			Self::String(s) => write!(f, "{:?}", s),
+			Self::InterpolatedString(_) => write!(f, "string"),
+			Self::Char(c) => write!(f, "{:?}", c),
+			Self::Ident(id) => write!(f, "{}", id),
+			Self::DocComment(_) => write!(f, "doc comment"),
+			Self::EOL => write!(f, "end of line"),
+			Self::Fn => write!(f, "fn"),
+			Self::Impl => write!(f, "impl"),
+			Self::Trait => write!(f, "trait"),
+			Self::Type => write!(f, "type"),
+			Self::For => write!(f, "for"),
+			Self::As => write!(f, "as"),
+			Self::True => write!(f, "true"),
+			Self::False => write!(f, "false"),
+			Self::LParen => write!(f, "("),
+			Self::RParen => write!(f, ")"),
+			Self::LBrace => write!(f, "{{"),
+			Self::RBrace => write!(f, "}}"),
+			Self::LBracket => write!(f, "["),
+			Self::RBracket => write!(f, "]"),
+			Self::Comma => write!(f, ","),
+			Self::Semicolon => write!(f, ";"),
+			Self::Colon => write!(f, ":"),
+			Self::Dot => write!(f, "."),
+			Self::Arrow => write!(f, "->"),
+			Self::Plus => write!(f, "+"),
+			Self::Minus => write!(f, "-"),
+			Self::Star => write!(f, "*"),
+			Self::StarStar => write!(f, "**"),
+			Self::Slash => write!(f, "/"),
+			Self::Percent => write!(f, "%"),
+			Self::Bang => write!(f, "!"),
+			Self::Assign => write!(f, "="),
+			Self::EqEq => write!(f, "=="),
+			Self::NotEq => write!(f, "!="),
+			Self::Lt => write!(f, "<"),
+			Self::Le => write!(f, "<="),
+			Self::Gt => write!(f, ">"),
+			Self::Ge => write!(f, ">="),
+			Self::Hash => write!(f, "#"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+	Str(String),
+	Expr(Vec<Span<Token>>),
 }
 
 enum State {
 	Normal,
 	Number(String, FilePosition),
-	String(String, bool, FilePosition),
+	String(String, FilePosition, Vec<StringPart>),
+	Char(String, FilePosition),
 	Ident(String, FilePosition),
+	/// `//` comment, the bool is true for `///` doc comments
+	LineComment(String, bool, FilePosition),
+	/// `/* */` comment, with its nesting depth and the previous char
+	BlockComment(usize, char, FilePosition),
 }
 
-fn line_col((line, line_i): (usize, usize), i: &usize) -> (usize, usize) {
-	(line, i - line_i + 1)
+/// The line being tokenized, gives the position of the chars in it
+#[derive(Clone, Copy)]
+struct Line<'a> {
+	number: usize,
+	/// Index of the first char of the line
+	start: usize,
+	/// Byte offset of each char of the source, and the length of the source after them
+	offsets: &'a [usize],
 }
 
-fn token<F, S, E>(token: Token, file: F, start: S, end: E) -> Span<Token>
+impl Line<'_> {
+	/// The line after this one, which starts at the char `start`
+	fn next(self, start: usize) -> Self {
+		Self {
+			number: self.number + 1,
+			start,
+			..self
+		}
+	}
+}
+
+/// Position of the char `i`
+fn line_col(line: Line, i: &usize) -> FilePosition {
+	FilePosition::new(line.number, i - line.start + 1, line.offsets[*i])
+}
+
+/// Position of the char `i` as the end of a location, so its offset is the byte after it
+fn end_col(line: Line, i: &usize) -> FilePosition {
+	FilePosition::new(line.number, i - line.start + 1, line.offsets[i + 1])
+}
+
+fn token<S, E>(token: Token, file: FileId, start: S, end: E) -> Span<Token>
 where
-	F: Into<SpanFile>,
 	S: Into<FilePosition>,
 	E: Into<FilePosition>,
 {
 	Span::new(token, Location::new(file, start, end))
 }
 
-pub fn tokenize<FProv: FileProvider<File>, File: FileReader>(file: &str, fprov: &FProv) -> Result<Vec<Span<Token>>, Error> {
-	let f = fprov.get_file(&file);
-	let s = f.contents();
+/// An identifier, or the keyword it spells
+fn ident_token(id: &str, file: FileId, start: FilePosition, end: FilePosition) -> Span<Token> {
+	let t = Token::keyword(id).unwrap_or_else(|| Token::Ident(id.to_string()));
+	token(t, file, start, end)
+}
+
+/// The punctuation accumulated in `kwd`, which always is a valid punctuator, as it is only extended while it stays one
+fn punct_token(kwd: &str, file: FileId, start: FilePosition) -> Span<Token> {
+	let t = Token::punct(kwd).expect("Kwd isn't a punctuator");
+	let end = FilePosition::new(start.line, start.col + kwd.chars().count() - 1, start.offset + kwd.len());
+	token(t, file, start, end)
+}
+
+pub fn tokenize(file: FileId, source_map: &SourceMap) -> Result<Vec<Span<Token>>, Error> {
+	tokenize_str(file, source_map.get(file).contents())
+}
+
+/// Moves a location inside a piece of source that was tokenized on its own to the position where it starts in the file
+fn shift_location(loc: &Location, start: FilePosition) -> Location {
+	let shift = |mut pos: FilePosition| {
+		if pos.line == 1 {
+			pos.col += start.col - 1;
+		}
+		pos.line += start.line - 1;
+		pos.offset += start.offset;
+		pos
+	};
+	Location::new(loc.file(), shift(loc.start), shift(loc.end))
+}
+
+/// Moves the tokens of a piece of source that was tokenized on its own to the position where it starts in the file
+fn shift_tokens(tokens: Vec<Span<Token>>, start: FilePosition) -> Vec<Span<Token>> {
+	tokens
+		.into_iter()
+		.map(|t| {
+			let loc = shift_location(t.loc(), start);
+			Span::new(t.unwrap(), loc)
+		})
+		.collect()
+}
+
+fn tokenize_str(file: FileId, s: &str) -> Result<Vec<Span<Token>>, Error> {
 	let mut tokens = Vec::new();
 	let mut state = State::Normal;
 	let mut chars = s.chars().enumerate().peekable();
 	let mut kwd = String::new();
 	let mut kwd_start = None;
-	let mut line = (1, 0);
+	let offsets = s.char_indices().map(|(i, _)| i).chain(std::iter::once(s.len())).collect::<Vec<usize>>();
+	let mut line = Line {
+		number: 1,
+		start: 0,
+		offsets: &offsets,
+	};
 	while let Some((i, peeked_char)) = chars.peek() {
-		if matches!(peeked_char, ' ' | '\t') && !matches!(state, State::String(_, _, _)) {
+		if peeked_char.is_whitespace() && peeked_char != &'\n' && !matches!(state, State::String(_, _, _) | State::Char(_, _) | State::LineComment(_, _, _) | State::BlockComment(_, _, _)) {
 			if let State::Ident(name, start) = &state {
-				tokens.push(token(
-					Token::Ident(name.clone()),
-					file,
-					*start,
-					line_col(line, &(i-1)),
-				));
+				tokens.push(ident_token(name, file, *start, end_col(line, &(i-1))));
+				state = State::Normal;
+			} else if let State::Number(num, start) = &state {
+				tokens.push(number_token(num, file, *start)?);
 				state = State::Normal;
 			} else if matches!(state, State::Normal) {
 				if !kwd.is_empty() {
-					tokens.push(token(Token::Kwd(kwd), file, kwd_start.expect("Kwd start not defined"), line_col(line, &(i - 1))));
+					tokens.push(punct_token(&kwd, file, kwd_start.expect("Kwd start not defined")));
 					kwd = String::new();
 					kwd_start = None;
 				}
@@ -70,11 +319,11 @@ pub fn tokenize<FProv: FileProvider<File>, File: FileReader>(file: &str, fprov:
 				// println!("NORMAL: {}", peeked_char);
 				if peeked_char.is_ascii_digit() {
 					if !kwd.is_empty() {
-						tokens.push(token(Token::Kwd(kwd), file, kwd_start.expect("Kwd start not defined"), line_col(line, i)));
+						tokens.push(punct_token(&kwd, file, kwd_start.expect("Kwd start not defined")));
 						kwd = String::new();
 						kwd_start = None;
 					}
-					state = State::Number(String::new(), line_col(line, i).into());
+					state = State::Number(String::new(), line_col(line, i));
 					continue;
 				}
 				// if peeked_char == &'(' {
@@ -84,84 +333,83 @@ pub fn tokenize<FProv: FileProvider<File>, File: FileReader>(file: &str, fprov:
 				// }
 				if peeked_char == &'"' {
 					if !kwd.is_empty() {
-						tokens.push(token(Token::Kwd(kwd), file, kwd_start.expect("Kwd start not defined"), line_col(line, i)));
-						kwd = String::new();
-						kwd_start = None;
-					}
-					state = State::String(String::new(), false, line_col(line, i).into());
-					chars.next();
-					continue;
-				}
-				// if let Ok(op) = Operator::try_from(peeked_char) {
-				// 	tokens.push(Token::BinaryOp(op));
-				// 	chars.next();
-				// 	continue;
-				// }
-				if peeked_char == &'\n' {
-					if !kwd.is_empty() {
-						tokens.push(token(Token::Kwd(kwd), file, kwd_start.expect("Kwd start not defined"), line_col(line, i)));
+						tokens.push(punct_token(&kwd, file, kwd_start.expect("Kwd start not defined")));
 						kwd = String::new();
 						kwd_start = None;
 					}
-					tokens.push(token(Token::EOL, file, line_col(line, i), line_col(line, i)));
-					line = (line.0 + 1, *i + 1);
+					state = State::String(String::new(), line_col(line, i), Vec::new());
 					chars.next();
 					continue;
 				}
-				if peeked_char == &';'{
+				if peeked_char == &'\'' {
 					if !kwd.is_empty() {
-						tokens.push(token(Token::Kwd(kwd), file, kwd_start.expect("Kwd start not defined"), line_col(line, i)));
+						tokens.push(punct_token(&kwd, file, kwd_start.expect("Kwd start not defined")));
 						kwd = String::new();
 						kwd_start = None;
 					}
-					tokens.push(token(Token::Colon, file, line_col(line, i), line_col(line, i)));
+					state = State::Char(String::new(), line_col(line, i));
 					chars.next();
 					continue;
 				}
-				if peeked_char == &',' {
-					if !kwd.is_empty() {
-						tokens.push(token(Token::Kwd(kwd), file, kwd_start.expect("Kwd start not defined"), line_col(line, i)));
-						kwd = String::new();
-						kwd_start = None;
-					}
-					tokens.push(token(Token::Comma, file, line_col(line, i), line_col(line, i)));
-					chars.next();
-					continue;
-				}
-				if peeked_char == &':' {
+				// if let Ok(op) = Operator::try_from(peeked_char) {
+				// 	tokens.push(Token::BinaryOp(op));
+				// 	chars.next();
+				// 	continue;
+				// }
+				if peeked_char == &'\n' {
 					if !kwd.is_empty() {
-						tokens.push(token(Token::Kwd(kwd), file, kwd_start.expect("Kwd start not defined"), line_col(line, i)));
+						tokens.push(punct_token(&kwd, file, kwd_start.expect("Kwd start not defined")));
 						kwd = String::new();
 						kwd_start = None;
 					}
-					tokens.push(token(Token::Semicolon, file, line_col(line, i), line_col(line, i)));
+					tokens.push(token(Token::EOL, file, line_col(line, i), end_col(line, i)));
+					line = line.next(*i + 1);
 					chars.next();
 					continue;
 				}
 				//tokens.push(Token::Other(*peeked_char));
-				if peeked_char.is_alphanumeric() {
+				if peeked_char.is_xid_start() || peeked_char == &'_' {
 					if !kwd.is_empty() {
-						tokens.push(token(Token::Kwd(kwd), file, kwd_start.expect("Kwd start not defined"), line_col(line, i)));
+						tokens.push(punct_token(&kwd, file, kwd_start.expect("Kwd start not defined")));
 						kwd = String::new();
 						kwd_start = None;
 					}
 					// println!("IDENT START:({:?}:{}) -> {:?}", line, i, line_col(line, i));
-					state = State::Ident(String::new(), line_col(line, i).into());
+					state = State::Ident(String::new(), line_col(line, i));
 					continue;
 				}
 				match *peeked_char {
-					'(' | ')' => {
+					'/' | '*' if kwd.ends_with('/') => {
+						// `//` or `/*` starts a comment
+						kwd.pop();
+						let start = kwd_start.take().expect("Kwd start not defined");
 						if !kwd.is_empty() {
-							tokens.push(token(Token::Kwd(kwd), file, kwd_start.expect("Kwd start not defined"), line_col(line, i)));
+							tokens.push(punct_token(&kwd, file, start));
 							kwd = String::new();
-							kwd_start = None;
 						}
-						let mut k = String::new();
-						k.push(*peeked_char);
-						tokens.push(token(Token::Kwd(k), file, line_col(line, i), line_col(line, i)))
+						let comment_start = line_col(line, &(i - 1));
+						state = if *peeked_char == '/' {
+							State::LineComment(String::new(), false, comment_start)
+						} else {
+							State::BlockComment(1, ' ', comment_start)
+						};
 					}
 					_ => {
-						kwd.push(*peeked_char);
+						let mut longer = kwd.clone();
+						longer.push(*peeked_char);
+						if Token::punct(&longer).is_none() {
+							// Maximal munch, the punctuator can't be extended with this char, so it ends here
+							if !kwd.is_empty() {
+								tokens.push(punct_token(&kwd, file, kwd_start.expect("Kwd start not defined")));
+								kwd_start = None;
+							}
+							longer = peeked_char.to_string();
+							if Token::punct(&longer).is_none() {
+								let err_span = Span::new((), Location::new(file, line_col(line, i), end_col(line, i)));
+								return Err(err_span.error(format!("Unknown character `{}`", peeked_char.escape_default()), ReturnValue::UnknownCharacter));
+							}
+						}
+						kwd = longer;
 						if kwd_start.is_none() {
 							kwd_start = Some(line_col(line, i))
 						}
@@ -171,51 +419,177 @@ pub fn tokenize<FProv: FileProvider<File>, File: FileReader>(file: &str, fprov:
 				chars.next();
 			}
 			State::Ident(id, start) => {
-				if peeked_char.is_alphabetic() || peeked_char == & '_' {
+				if id == "r" && matches!(peeked_char, '"' | '#') {
+					// raw string, `r"..."` or `r#"..."#`
+					let start = *start;
+					tokens.push(read_raw_string(&mut chars, file, &mut line, start)?);
+					state = State::Normal;
+				} else if peeked_char.is_xid_continue() {
 					id.push(*peeked_char);
 					chars.next();
 				} else {
 					// println!("Ident end ({})", peeked_char);
-					tokens.push(token(Token::Ident(id.clone()), file, *start, line_col(line, &(i-1))));
+					tokens.push(ident_token(id, file, *start, end_col(line, &(i-1))));
+					state = State::Normal;
+				}
+			}
+			State::LineComment(s, is_doc, start) => {
+				if peeked_char == &'\n' {
+					if *is_doc {
+						let doc = s.strip_prefix(' ').unwrap_or(s).trim_end().to_string();
+						tokens.push(token(Token::DocComment(doc), file, *start, end_col(line, &(i - 1))));
+					}
 					state = State::Normal;
+					continue;
+				}
+				if peeked_char == &'/' && s.is_empty() && !*is_doc {
+					// `///` is a doc comment, but `////` is a regular one
+					*is_doc = true;
+				} else if peeked_char == &'/' && s.is_empty() && *is_doc {
+					*is_doc = false;
+					s.push('/');
+				} else {
+					s.push(*peeked_char);
+				}
+				chars.next();
+			}
+			State::BlockComment(depth, prev, _) => {
+				let c = *peeked_char;
+				if c == '\n' {
+					line = line.next(*i + 1);
+				}
+				chars.next();
+				if *prev == '/' && c == '*' {
+					*depth += 1;
+					*prev = ' ';
+				} else if *prev == '*' && c == '/' {
+					*depth -= 1;
+					*prev = ' ';
+					if *depth == 0 {
+						state = State::Normal;
+					}
+				} else {
+					*prev = c;
 				}
 			}
 			State::Number(num, start) => {
-				if peeked_char.is_ascii_digit() || (peeked_char == &'.' && !num.contains('.')) {
+				let exponent_sign = matches!(peeked_char, '+' | '-')
+					&& (num.ends_with('e') || num.ends_with('E'))
+					&& !num.starts_with("0x")
+					&& !num.starts_with("0X");
+				if peeked_char.is_alphanumeric() || matches!(peeked_char, '_' | '.') || exponent_sign {
 					num.push(*peeked_char);
 					chars.next();
 				} else {
-					if let Ok(n) = num.parse() {
-						tokens.push(token(Token::Number(n), file, *start, line_col(line, &(*i-1))))
-					} else {
-						let err_span = Span::new(num.clone(), Location::new(file, *start, line_col(line, i)));
-						return Err(err_span.error("Number wrongly formatted", ReturnValue::WrongNumberFormat));
-					}
+					tokens.push(number_token(num, file, *start)?);
 					state = State::Normal;
 				}
 			}
-			State::String(s, escaping, start) => {
-				if *escaping {
-					*escaping = false;
-					if let Some(c) = get_escape_char(peeked_char) {
-						s.pop();
-						s.push(c);
-						chars.next();
-						continue;
+			State::String(s, start, parts) => {
+				if peeked_char == &'\\' {
+					let (backslash_i, _) = chars.next().unwrap();
+					s.push(read_escape(&mut chars, file, line, backslash_i)?);
+					continue;
+				}
+				if peeked_char == &'\n' {
+					// Strings can span multiple lines
+					line = line.next(*i + 1);
+					s.push('\n');
+					chars.next();
+					continue;
+				}
+				if peeked_char == &'"' {
+					let t = if parts.is_empty() {
+						Token::String(s.clone())
 					} else {
-						s.push(*peeked_char);
+						if !s.is_empty() {
+							parts.push(StringPart::Str(s.clone()));
+						}
+						Token::InterpolatedString(parts.clone())
+					};
+					tokens.push(token(t, file, *start, end_col(line, i)));
+					state = State::Normal;
+					chars.next();
+					continue;
+				}
+				if peeked_char == &'{' || peeked_char == &'}' {
+					let (brace_i, brace) = chars.next().unwrap();
+					if chars.peek().map(|(_, c)| c) == Some(&brace) {
+						// `{{` and `}}` are escaped braces
+						s.push(brace);
 						chars.next();
 						continue;
 					}
+					if brace == '}' {
+						let err_span = Span::new((), Location::new(file, line_col(line, &brace_i), end_col(line, &brace_i)));
+						return Err(err_span.error("Unmatched `}` in string, use `}}` to write a literal `}`", ReturnValue::UnclosedString));
+					}
+					let expr_start = line_col(line, &(brace_i + 1));
+					let mut expr = String::new();
+					let mut depth = 0;
+					// The quote of the string or char literal the expression is in, braces inside them don't count
+					let mut quote = None;
+					loop {
+						match chars.next() {
+							Some((_, '}')) if depth == 0 && quote.is_none() => break,
+							Some((_, '\n')) | None => {
+								let err_span = Span::new((), Location::new(file, line_col(line, &brace_i), end_col(line, &brace_i)));
+								return Err(err_span.error("Unclosed `{` in string interpolation", ReturnValue::UnclosedBracket));
+							}
+							Some((_, c)) => {
+								expr.push(c);
+								match (quote, c) {
+									(Some(_), '\\') => {
+										// The escaped char can't close the literal, the escape itself is checked when the expression is tokenized
+										if let Some((_, escaped)) = chars.next_if(|(_, c)| *c != '\n') {
+											expr.push(escaped);
+										}
+									}
+									(Some(q), c) if c == q => quote = None,
+									(Some(_), _) => (),
+									(None, '"' | '\'') => quote = Some(c),
+									(None, '{') => depth += 1,
+									(None, '}') => depth -= 1,
+									_ => (),
+								}
+							}
+						}
+					}
+					if expr.trim().is_empty() {
+						let err_span = Span::new((), Location::new(file, line_col(line, &brace_i), end_col(line, &(brace_i + 1))));
+						return Err(err_span.error("Expected an expression inside `{}`", ReturnValue::UnexpectedNonValue));
+					}
+					if !s.is_empty() {
+						parts.push(StringPart::Str(s.clone()));
+						s.clear();
+					}
+					let expr_tokens = tokenize_str(file, &expr).map_err(|e| {
+							let loc = shift_location(e.loc(), expr_start);
+							e.with_loc(loc)
+						})?;
+						parts.push(StringPart::Expr(shift_tokens(expr_tokens, expr_start)));
+					continue;
 				}
+				s.push(*peeked_char);
+				chars.next();
+			}
+			State::Char(s, start) => {
 				if peeked_char == &'\\' {
-					s.push(*peeked_char);
-					chars.next();
-					*escaping = true;
+					let (backslash_i, _) = chars.next().unwrap();
+					s.push(read_escape(&mut chars, file, line, backslash_i)?);
 					continue;
 				}
-				if peeked_char == &'"' {
-					tokens.push(token(Token::String(s.clone()), file, *start, line_col(line, i)));
+				if peeked_char == &'\'' || peeked_char == &'\n' {
+					let err_span = Span::new(s.clone(), Location::new(file, *start, end_col(line, i)));
+					if peeked_char == &'\n' {
+						return Err(err_span.error("Unclosed char literal", ReturnValue::InvalidCharLiteral));
+					}
+					let mut c = s.chars();
+					if let (Some(c), None) = (c.next(), c.next()) {
+						tokens.push(token(Token::Char(c), file, *start, end_col(line, i)));
+					} else {
+						return Err(err_span.error("Char literals must contain exactly one character", ReturnValue::InvalidCharLiteral));
+					}
 					state = State::Normal;
 					chars.next();
 					continue;
@@ -226,32 +600,254 @@ pub fn tokenize<FProv: FileProvider<File>, File: FileReader>(file: &str, fprov:
 		};
 	}
 	if let State::Number(num, start) = state {
-		if let Ok(n) = num.parse() {
-			tokens.push(token(Token::Number(n), file, start, line_col(line, &(s.len()-1))))
-		} else {
-			let err_span = Span::new(num, Location::new(file, start, line_col(line, &(s.len()-1))));
-			return Err(err_span.error("Number wrongly formatted", ReturnValue::WrongNumberFormat));
-			//panic!("Error parsing number")
-		}
+		tokens.push(number_token(&num, file, start)?);
 	}else if let State::Ident(name, start) = state {
-		tokens.push(token(Token::Ident(name), file, start, line_col(line, &(s.len()-1))))
-	} else if let State::String(v, _escaping, start) = state {
-		let err_span = Span::new(v, Location::new(file, start, line_col(line, &(s.len()-1))));
+		tokens.push(ident_token(&name, file, start, end_col(line, &(s.chars().count()-1))))
+	} else if let State::String(v, start, _) = state {
+		let err_span = Span::new(v, Location::new(file, start, end_col(line, &(s.chars().count()-1))));
 		return Err(err_span.error("Unclosed string", ReturnValue::UnclosedString));
+	} else if let State::Char(v, start) = state {
+		let err_span = Span::new(v, Location::new(file, start, end_col(line, &(s.chars().count()-1))));
+		return Err(err_span.error("Unclosed char literal", ReturnValue::InvalidCharLiteral));
+	} else if let State::LineComment(v, true, start) = state {
+		let doc = v.strip_prefix(' ').unwrap_or(&v).trim_end().to_string();
+		tokens.push(token(Token::DocComment(doc), file, start, end_col(line, &(s.chars().count()-1))))
+	} else if let State::BlockComment(_, _, start) = state {
+		let err_span = Span::new((), Location::new(file, start, start));
+		return Err(err_span.error("Unclosed block comment, expected `*/`", ReturnValue::UnclosedComment));
 	}else{
 		if let Some(start) = kwd_start {
-			tokens.push(token(Token::Kwd(kwd), file, start, line_col(line, &(s.len()-1))))
+			tokens.push(punct_token(&kwd, file, start))
 		}
 	}
 	//println!("");
 	Ok(tokens)
 }
 
+/// Parses a number literal: `1_000`, `1.5e-3`, `0xFF`, `0b1010`, `0o17`, and their bigint version with a `n` suffix.
+/// Errors point to the invalid part of the literal
+fn number_token(num: &str, file: FileId, start: FilePosition) -> Result<Span<Token>, Error> {
+	let digits: Vec<char> = num.chars().collect();
+	// Byte offset of the char `k` of the literal
+	let byte = |k: usize| num.char_indices().nth(k).map_or(num.len(), |(b, _)| b);
+	let err = |from: usize, to: usize, info: String| {
+		let s = FilePosition::new(start.line, start.col + from, start.offset + byte(from));
+		let e = FilePosition::new(start.line, start.col + to, start.offset + byte(to + 1));
+		Span::new(num.to_string(), Location::new(file, s, e)).error(info, ReturnValue::WrongNumberFormat)
+	};
+	let end = FilePosition::new(start.line, start.col + digits.len() - 1, start.offset + num.len());
+
+	let (radix, name, body_start) = match (digits.first(), digits.get(1)) {
+		(Some('0'), Some('x' | 'X')) => (16, "hexadecimal", 2),
+		(Some('0'), Some('o' | 'O')) => (8, "octal", 2),
+		(Some('0'), Some('b' | 'B')) => (2, "binary", 2),
+		_ => (10, "decimal", 0),
+	};
+	let is_bigint = digits.last() == Some(&'n');
+	let body_end = if is_bigint { digits.len() - 1 } else { digits.len() };
+
+	let mut cleaned = String::new();
+	let mut decimal_point = None;
+	let mut exponent = None;
+	for (k, c) in digits.iter().enumerate().take(body_end).skip(body_start) {
+		match c {
+			'_' => (),
+			c if c.is_digit(radix) => cleaned.push(*c),
+			'.' if radix != 10 => {
+				return Err(err(k, k, format!("The {} literal `{}` can't have a decimal part", name, num)));
+			}
+			'.' => {
+				if let Some(e) = exponent {
+					return Err(err(e, k, "Unexpected `.` after the exponent".to_string()));
+				}
+				if let Some(p) = decimal_point {
+					return Err(err(p, k, "Number literals can only have one decimal point".to_string()));
+				}
+				decimal_point = Some(k);
+				cleaned.push('.');
+			}
+			'e' | 'E' if radix == 10 => {
+				if let Some(e) = exponent {
+					return Err(err(e, k, "Number literals can only have one exponent".to_string()));
+				}
+				exponent = Some(k);
+				cleaned.push('e');
+			}
+			'+' | '-' => cleaned.push(*c),
+			c => return Err(err(k, k, format!("Invalid digit `{}` in {} literal `{}`", c, name, num))),
+		}
+	}
+	if let Some(e) = exponent {
+		if !digits[e + 1..body_end].iter().any(|c| c.is_ascii_digit()) {
+			return Err(err(e, body_end - 1, "Missing the exponent digits after `e`".to_string()));
+		}
+	}
+	if !cleaned.chars().any(|c| c.is_digit(radix)) {
+		return Err(err(0, digits.len() - 1, format!("Missing digits after the {} prefix `{}`", name, &num[..2])));
+	}
+
+	if is_bigint {
+		if let Some(p) = decimal_point {
+			return Err(err(p, body_end - 1, "Bigint literals can't have a decimal part".to_string()));
+		}
+		if let Some(e) = exponent {
+			return Err(err(e, body_end - 1, "Bigint literals can't have an exponent".to_string()));
+		}
+		let n = num_bigint::BigInt::parse_bytes(cleaned.as_bytes(), radix)
+			.ok_or_else(|| err(0, digits.len() - 1, "Number wrongly formatted".to_string()))?;
+		return Ok(token(Token::BigInt(n), file, start, end));
+	}
+	let n = if radix == 10 {
+		cleaned.parse().map_err(|_| err(0, digits.len() - 1, "Number wrongly formatted".to_string()))?
+	} else {
+		u64::from_str_radix(&cleaned, radix).map_err(|_| {
+			err(0, digits.len() - 1, format!("The {} literal `{}` is too large, use a bigint literal (`{}n`)", name, num, num))
+		})? as f64
+	};
+	Ok(token(Token::Number(n), file, start, end))
+}
+
+/// Reads a raw string (`r"..."`, `r#"..."#`), `chars` must be right after the `r`
+/// Raw strings have no escapes nor interpolation, and end at the first `"` followed by as many `#` as the start
+fn read_raw_string<I: Iterator<Item = (usize, char)> + Clone>(
+	chars: &mut Peekable<I>,
+	file: FileId,
+	line: &mut Line,
+	start: FilePosition,
+) -> Result<Span<Token>, Error> {
+	let mut hashes = 0;
+	while let Some((_, '#')) = chars.peek() {
+		hashes += 1;
+		chars.next();
+	}
+	match chars.next() {
+		Some((_, '"')) => (),
+		Some((i, _)) => {
+			let err_span = Span::new((), Location::new(file, start, end_col(*line, &i)));
+			return Err(err_span.error("Expected `\"` to start the raw string", ReturnValue::UnclosedString));
+		}
+		None => {
+			let err_span = Span::new((), Location::new(file, start, start));
+			return Err(err_span.error("Expected `\"` to start the raw string", ReturnValue::UnclosedString));
+		}
+	}
+	let mut s = String::new();
+	while let Some((i, c)) = chars.next() {
+		if c == '"' {
+			let mut ahead = chars.clone();
+			let mut closing = 0;
+			while closing < hashes && matches!(ahead.next(), Some((_, '#'))) {
+				closing += 1;
+			}
+			if closing == hashes {
+				let mut end = i;
+				for _ in 0..hashes {
+					end = chars.next().unwrap().0;
+				}
+				return Ok(token(Token::String(s), file, start, end_col(*line, &end)));
+			}
+		}
+		if c == '\n' {
+			*line = line.next(i + 1);
+		}
+		s.push(c);
+	}
+	let err_span = Span::new(s, Location::new(file, start, start));
+	Err(err_span.error("Unclosed raw string", ReturnValue::UnclosedString))
+}
+
+/// Reads an escape sequence, `chars` must be right after the `\` (which is at `backslash_i`)
+fn read_escape<I: Iterator<Item = (usize, char)>>(
+	chars: &mut Peekable<I>,
+	file: FileId,
+	line: Line,
+	backslash_i: usize,
+) -> Result<char, Error> {
+	let err = |end: usize, info: String| {
+		Span::new((), Location::new(file, line_col(line, &backslash_i), end_col(line, &end)))
+			.error(info, ReturnValue::InvalidEscape)
+	};
+	match chars.next() {
+		Some((i, 'u')) => {
+			match chars.next() {
+				Some((_, '{')) => (),
+				_ => return Err(err(i, "Expected `{` after `\\u`, unicode escapes are written as `\\u{7FFF}`".into())),
+			}
+			let mut hex = String::new();
+			loop {
+				match chars.next() {
+					Some((i, '}')) => {
+						return u32::from_str_radix(&hex, 16)
+							.ok()
+							.filter(|_| hex.len() <= 6)
+							.and_then(char::from_u32)
+							.ok_or_else(|| err(i, format!("`{}` isn't a valid unicode scalar value", hex)));
+					}
+					Some((_, c)) if c.is_ascii_hexdigit() => hex.push(c),
+					Some((i, '\n')) | Some((i, '"')) => return Err(err(i - 1, "Unclosed unicode escape, expected `}`".into())),
+					Some((i, c)) => {
+						return Err(err(i, format!("Unexpected `{}` in unicode escape, expected an hexadecimal digit", c)))
+					}
+					None => return Err(err(backslash_i + 1, "Unclosed unicode escape, expected `}`".into())),
+				}
+			}
+		}
+		Some((i, c)) => get_escape_char(&c).ok_or_else(|| err(i, format!("Unknown escape `\\{}`", c.escape_default()))),
+		None => Err(err(backslash_i, "Expected an escaped character".into())),
+	}
+}
+
 fn get_escape_char(escaped: &char) -> Option<char> {
 	match escaped {
 		'n' => Some('\n'),
+		't' => Some('\t'),
+		'r' => Some('\r'),
+		'0' => Some('\0'),
 		'\\' => Some('\\'),
 		'"' => Some('"'),
+		'\'' => Some('\''),
 		_ => None,
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn lex(s: &str) -> Vec<Token> {
+		let file = SourceMap::new().add("test.lang".into(), s.to_string());
+		tokenize_str(file, s)
+			.expect("Error tokenizing")
+			.into_iter()
+			.map(Span::unwrap)
+			.collect()
+	}
+
+	#[test]
+	fn numbers_end_at_whitespace() {
+		let cast = |n| vec![Token::Number(n), Token::As, Token::Ident("integer".into())];
+		assert_eq!(lex("2 as integer"), cast(2.0));
+		assert_eq!(lex("0x1F as integer"), cast(31.0));
+		assert_eq!(lex("1_000\t2e3"), vec![Token::Number(1000.0), Token::Number(2000.0)]);
+	}
+
+	#[test]
+	fn interpolation_skips_literals() {
+		let parts = |s: &str| match lex(s).remove(0) {
+			Token::InterpolatedString(parts) => parts
+				.into_iter()
+				.map(|p| match p {
+					StringPart::Str(s) => vec![Token::String(s)],
+					StringPart::Expr(tokens) => tokens.into_iter().map(Span::unwrap).collect(),
+				})
+				.collect::<Vec<_>>(),
+			t => panic!("Expected an interpolated string, found {:?}", t),
+		};
+		assert_eq!(parts(r#""{'}'}""#), vec![vec![Token::Char('}')]]);
+		assert_eq!(parts(r#""{'\''} {"\"{{"}""#), vec![
+			vec![Token::Char('\'')],
+			vec![Token::String(" ".into())],
+			vec![Token::String("\"{".into())],
+		]);
+	}
+}
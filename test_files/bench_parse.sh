@@ -0,0 +1,22 @@
+#!/bin/sh
+# Measures how the tokenizer and parser scale: generates files of growing size with gen_bench.sh and prints the
+# times the release build reports for each one. The times should grow linearly with the number of lines.
+# The compiler is stopped once it has parsed, as checking isn't what's measured.
+#   sh test_files/bench_parse.sh [lines...]
+cargo build --release -q || exit 1
+dir=$(mktemp -d)
+printf '%8s %12s %12s\n' lines tokenize parse
+for lines in ${@:-25000 50000 100000}; do
+	sh test_files/gen_bench.sh "$lines" > "$dir/bench.lang"
+	target/release/lang --lib "$dir/bench.lang" > "$dir/out.txt" 2>/dev/null &
+	pid=$!
+	while kill -0 "$pid" 2>/dev/null && ! grep -q '^Parse time' "$dir/out.txt"; do
+		sleep 0.1
+	done
+	kill "$pid" 2>/dev/null
+	wait "$pid" 2>/dev/null
+	tok=$(sed -n 's/^Tokenization time: //p' "$dir/out.txt")
+	parse=$(sed -n 's/^Parse time: //p' "$dir/out.txt")
+	printf '%8s %12s %12s\n' "$lines" "$tok" "$parse"
+done
+rm -r "$dir"
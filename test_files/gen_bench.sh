@@ -0,0 +1,16 @@
+#!/bin/sh
+# Generates a file with around `lines` lines (100000 by default) to benchmark the tokenizer and parser:
+#   sh test_files/gen_bench.sh > bench.lang && cargo run --release -- --lib bench.lang
+lines=${1:-100000}
+awk -v lines="$lines" 'BEGIN {
+	fns = int(lines / 1002)
+	if (fns < 1) fns = 1
+	for (f = 0; f < fns; f++) {
+		printf "/// Generated function number %d\n", f
+		printf "fn f%d(x%d: number) -> number {\n", f, f
+		for (l = 0; l < 1000; l++) {
+			printf "\t(x%d + %d) * 2 - x%d / 4 ** 2 ** 1 == %d; // statement %d\n", f, l, f, l, l
+		}
+		printf "\tx%d\n}\n", f
+	}
+}'
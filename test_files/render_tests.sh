@@ -0,0 +1,27 @@
+#!/bin/sh
+# Checks how errors are shown: runs the compiler on every test_files/render/*.lang and compares what it writes to stderr
+# with the .stderr file next to it, and its exit status with the .status file. The options in the .args file next to it,
+# if there is one, are used too.
+#   cargo build && sh test_files/render_tests.sh
+# With `--bless` the .stderr files are written with the current output instead
+bin=${BIN:-target/debug/lang}
+failed=0
+for src in test_files/render/*.lang; do
+	expected="${src%.lang}.stderr"
+	args=""
+	[ -f "${src%.lang}.args" ] && args=$(cat "${src%.lang}.args")
+	actual=$("$bin" --color=never $args "$src" 2>&1 >/dev/null)
+	status=$?
+	if [ "$1" = "--bless" ]; then
+		printf '%s\n' "$actual" > "$expected"
+		echo "$status" > "${src%.lang}.status"
+	elif [ "$actual" != "$(cat "$expected")" ]; then
+		echo "FAILED $src"
+		printf '%s\n' "$actual" | diff "$expected" -
+		failed=1
+	elif [ "$status" != "$(cat "${src%.lang}.status")" ]; then
+		echo "FAILED $src: exit status $status, expected $(cat "${src%.lang}.status")"
+		failed=1
+	fi
+done
+exit $failed